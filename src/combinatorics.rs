@@ -9,8 +9,12 @@ fn fill_bins(iteration: u64, bins: u8, max_items_per_bin: u8) -> Vec<u8> {
     .collect()
 }
 
+pub fn combinations_count(bins: u8, max_items_per_bin: u8) -> u64 {
+    u64::from(max_items_per_bin).pow(u32::from(bins))
+}
+
 pub fn get_combinations(bins: u8, max_items_per_bin: u8) -> impl Iterator<Item = Vec<u8>> {
-    let iterations_num = u64::from(max_items_per_bin).pow(u32::from(bins));
+    let iterations_num = combinations_count(bins, max_items_per_bin);
     (0..iterations_num).map(move |iteration| fill_bins(iteration, bins, max_items_per_bin))
 }
 
@@ -26,6 +30,7 @@ mod tests {
         assert_eq!(get_combinations(3, 6).count(), 216);
         assert_eq!(get_combinations(4, 6).count(), 1296);
         assert_eq!(get_combinations(5, 6).count(), 7776);
+        assert_eq!(combinations_count(5, 6), 7776);
     }
 
     #[test]
@@ -54,7 +59,7 @@ mod tests {
         assert!(combinations.contains(&vec!(5u8, 5u8, 5u8, 5u8)));
         assert!(combinations.contains(&vec!(2u8, 3u8, 1u8, 4u8)));
 
-        assert_eq!(false, combinations.contains(&vec!(0u8, 0u8, 0u8, 6u8)));
-        assert_eq!(false, combinations.contains(&vec!(6u8, 6u8, 6u8, 6u8)));
+        assert!(!combinations.contains(&vec!(0u8, 0u8, 0u8, 6u8)));
+        assert!(!combinations.contains(&vec!(6u8, 6u8, 6u8, 6u8)));
     }
 }
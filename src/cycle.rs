@@ -0,0 +1,241 @@
+use crate::game::{Move, Payoff};
+use crate::player::GamePlay;
+use crate::strategy::Strategy;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchMode {
+    // Plays every turn.
+    Simulate,
+    // Stops playing once both players are back in a state they were in
+    // before and extrapolates the rest of the match from the cycle between.
+    // Gives exactly the same summary as simulation. A strategy that would
+    // panic after the cycle is never asked, so its fault goes unnoticed;
+    // matches with a time budget are always simulated.
+    Extrapolate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cycle {
+    // Turn at which the cycle starts for the first time.
+    pub start: u64,
+    pub length: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchSummary {
+    pub turns: u64,
+    pub scores: (u64, u64),
+    pub cooperations: (u64, u64),
+    // The cycle the match was extrapolated from, if any.
+    pub cycle: Option<Cycle>,
+}
+
+// Running totals after some turns of a match.
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    scores: (u64, u64),
+    cooperations: (u64, u64),
+}
+
+impl Totals {
+    fn add(self, other: Totals, times: u64) -> Self {
+        Self {
+            scores: (
+                self.scores.0 + times * other.scores.0,
+                self.scores.1 + times * other.scores.1,
+            ),
+            cooperations: (
+                self.cooperations.0 + times * other.cooperations.0,
+                self.cooperations.1 + times * other.cooperations.1,
+            ),
+        }
+    }
+
+    fn since(self, earlier: Totals) -> Self {
+        Self {
+            scores: (
+                self.scores.0 - earlier.scores.0,
+                self.scores.1 - earlier.scores.1,
+            ),
+            cooperations: (
+                self.cooperations.0 - earlier.cooperations.0,
+                self.cooperations.1 - earlier.cooperations.1,
+            ),
+        }
+    }
+}
+
+// Plays a noiseless match of `turns` turns between two strategies and sums it
+// up. In extrapolation mode, a match costs as many turns as it takes both
+// players to repeat a joint state, however long it is; once the state of a
+// player is unknown, the rest of the match is simply simulated.
+pub fn summarize_match(
+    first: &dyn Strategy,
+    second: &dyn Strategy,
+    payoff: &Payoff,
+    turns: u64,
+    mode: MatchMode,
+) -> MatchSummary {
+    let mut first_history = Vec::new();
+    let mut second_history = Vec::new();
+    // `totals[t]` holds the totals after `t` turns, only kept while a cycle
+    // can still be found.
+    let mut totals = vec![Totals::default()];
+    let mut last = Totals::default();
+    let mut seen = HashMap::new();
+    let mut extrapolating = mode == MatchMode::Extrapolate;
+    for turn in 0..turns {
+        if extrapolating {
+            let states = (first.state(&first_history), second.state(&second_history));
+            if let (Some(first_state), Some(second_state)) = states {
+                if let Some(start) = seen.insert((first_state, second_state), turn) {
+                    return extrapolate(
+                        &totals,
+                        Cycle {
+                            start,
+                            length: turn - start,
+                        },
+                        turns,
+                    );
+                }
+            } else {
+                extrapolating = false;
+                totals = Vec::new();
+                seen = HashMap::new();
+            }
+        }
+        let first_move = first.play(&first_history);
+        let second_move = second.play(&second_history);
+        let scores = payoff.compute_payoff(first_move, second_move);
+        first_history.push(GamePlay {
+            my_move: first_move,
+            their_move: second_move,
+        });
+        second_history.push(GamePlay {
+            my_move: second_move,
+            their_move: first_move,
+        });
+        last = last.add(
+            Totals {
+                scores: (scores.0.into(), scores.1.into()),
+                cooperations: (
+                    u64::from(first_move == Move::Collaborate),
+                    u64::from(second_move == Move::Collaborate),
+                ),
+            },
+            1,
+        );
+        if extrapolating {
+            totals.push(last);
+        }
+    }
+    MatchSummary {
+        turns,
+        scores: last.scores,
+        cooperations: last.cooperations,
+        cycle: None,
+    }
+}
+
+fn extrapolate(totals: &[Totals], cycle: Cycle, turns: u64) -> MatchSummary {
+    let played = cycle.start + cycle.length;
+    let at = |turn: u64| totals[turn as usize];
+    let per_cycle = at(played).since(at(cycle.start));
+    let remaining = turns - played;
+    let rest = at(cycle.start + remaining % cycle.length).since(at(cycle.start));
+    let last = at(played)
+        .add(per_cycle, remaining / cycle.length)
+        .add(rest, 1);
+    MatchSummary {
+        turns,
+        scores: last.scores,
+        cooperations: last.cooperations,
+        cycle: Some(cycle),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::all_strategies;
+    use crate::strategy::drunk::Drunk;
+    use crate::strategy::grim_trigger::GrimTrigger;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::random::Random;
+    use crate::strategy::tit_for_tat::TitForTat;
+
+    #[test]
+    fn extrapolation_matches_simulation() {
+        let strategies = all_strategies();
+        let payoff = Payoff::default();
+        for first in strategies.iter().filter(|s| s.is_deterministic()) {
+            for second in strategies.iter().filter(|s| s.is_deterministic()) {
+                for turns in [0, 1, 2, 3, 7, 100, 1001] {
+                    let play = |mode| {
+                        summarize_match(first.as_ref(), second.as_ref(), &payoff, turns, mode)
+                    };
+                    let simulated = play(MatchMode::Simulate);
+                    let extrapolated = play(MatchMode::Extrapolate);
+                    assert_eq!(simulated.scores, extrapolated.scores);
+                    assert_eq!(simulated.cooperations, extrapolated.cooperations);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn billion_turns() {
+        let summary = summarize_match(
+            &Drunk {},
+            &TitForTat {},
+            &Payoff::default(),
+            1_000_000_000,
+            MatchMode::Extrapolate,
+        );
+        // Drunk alternates C, D while TitForTat copies it one turn later: the
+        // turns pay (3, 3), then (5, 0), (0, 5), (5, 0) and so on, ending on
+        // (5, 0).
+        assert_eq!(
+            summary.cycle,
+            Some(Cycle {
+                start: 1,
+                length: 2
+            })
+        );
+        assert_eq!(summary.scores, (2_500_000_003, 2_499_999_998));
+        assert_eq!(summary.cooperations, (500_000_000, 500_000_001));
+    }
+
+    #[test]
+    fn grim_trigger_against_nasty() {
+        let summary = summarize_match(
+            &GrimTrigger {},
+            &Nasty {},
+            &Payoff::default(),
+            1_000_000,
+            MatchMode::Extrapolate,
+        );
+        assert_eq!(
+            summary.cycle,
+            Some(Cycle {
+                start: 1,
+                length: 1
+            })
+        );
+        assert_eq!(summary.scores, (999_999, 5 + 999_999));
+    }
+
+    #[test]
+    fn unknown_states_are_simulated() {
+        let summary = summarize_match(
+            &Random {},
+            &Nasty {},
+            &Payoff::default(),
+            50,
+            MatchMode::Extrapolate,
+        );
+        assert_eq!(summary.cycle, None);
+        assert_eq!(summary.cooperations.1, 0);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::game::Move;
+use crate::player::GamePlay;
+use crate::strategy::Strategy;
+use std::collections::VecDeque;
+use std::fmt;
+
+// The shortest history, seen from the point of view of the compared
+// strategies, after which they choose different moves.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub history: Vec<GamePlay>,
+    pub first_move: Move,
+    pub second_move: Move,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Equivalence {
+    Identical,
+    Divergent(Divergence),
+}
+
+impl fmt::Display for Equivalence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Equivalence::Identical => write!(f, "behaviorally identical"),
+            Equivalence::Divergent(divergence) => {
+                write!(f, "diverge after history [")?;
+                for (i, game) in divergence.history.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}{}", game.my_move, game.their_move)?;
+                }
+                write!(
+                    f,
+                    "]: first plays {}, second plays {}",
+                    divergence.first_move, divergence.second_move
+                )
+            }
+        }
+    }
+}
+
+// Compares two strategies by querying them on every history in which the
+// opponent played any sequence of moves of at most `depth` turns, and then by
+// playing both against each probe for `probe_turns` turns. Strategies that
+// draw random moves will usually be reported as divergent.
+pub fn compare(
+    first: &dyn Strategy,
+    second: &dyn Strategy,
+    depth: usize,
+    probes: &[Box<dyn Strategy>],
+    probe_turns: usize,
+) -> Equivalence {
+    if let Some(divergence) = exhaustive_divergence(first, second, depth) {
+        return Equivalence::Divergent(divergence);
+    }
+    probes
+        .iter()
+        .filter_map(|probe| probe_divergence(first, second, probe.as_ref(), probe_turns))
+        .min_by_key(|divergence| divergence.history.len())
+        .map_or(Equivalence::Identical, Equivalence::Divergent)
+}
+
+fn divergence(
+    first: &dyn Strategy,
+    second: &dyn Strategy,
+    history: &[GamePlay],
+) -> Option<Divergence> {
+    let first_move = first.play(history);
+    let second_move = second.play(history);
+    if first_move == second_move {
+        None
+    } else {
+        Some(Divergence {
+            history: history.to_vec(),
+            first_move,
+            second_move,
+        })
+    }
+}
+
+// Breadth first, so the first divergence found is also the shortest one.
+fn exhaustive_divergence(
+    first: &dyn Strategy,
+    second: &dyn Strategy,
+    depth: usize,
+) -> Option<Divergence> {
+    let mut histories = VecDeque::from([Vec::new()]);
+    while let Some(history) = histories.pop_front() {
+        if let Some(divergence) = divergence(first, second, &history) {
+            return Some(divergence);
+        }
+        if history.len() < depth {
+            let my_move = first.play(&history);
+            for their_move in [Move::Collaborate, Move::Defect] {
+                let mut next = history.clone();
+                next.push(GamePlay {
+                    my_move,
+                    their_move,
+                });
+                histories.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+fn probe_divergence(
+    first: &dyn Strategy,
+    second: &dyn Strategy,
+    probe: &dyn Strategy,
+    turns: usize,
+) -> Option<Divergence> {
+    let mut history = Vec::with_capacity(turns);
+    let mut probe_history = Vec::with_capacity(turns);
+    for _ in 0..turns {
+        if let Some(divergence) = divergence(first, second, &history) {
+            return Some(divergence);
+        }
+        let my_move = first.play(&history);
+        let their_move = probe.play(&probe_history);
+        history.push(GamePlay {
+            my_move,
+            their_move,
+        });
+        probe_history.push(GamePlay {
+            my_move: their_move,
+            their_move: my_move,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::all_strategies;
+    use crate::strategy::grim_trigger::GrimTrigger;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+
+    // Friedman's rule written without looking at the whole history: keep
+    // defecting once either side has defected in the previous turn.
+    struct Friedman {}
+
+    impl Strategy for Friedman {
+        fn play(&self, past_games: &[GamePlay]) -> Move {
+            match past_games.last() {
+                None => Move::Collaborate,
+                Some(last_game) => {
+                    if last_game.my_move == Move::Defect || last_game.their_move == Move::Defect {
+                        Move::Defect
+                    } else {
+                        Move::Collaborate
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grim_trigger_and_friedman_are_identical() {
+        let equivalence = compare(&GrimTrigger {}, &Friedman {}, 8, &all_strategies(), 50);
+        assert_eq!(equivalence, Equivalence::Identical);
+    }
+
+    #[test]
+    fn naive_and_tit_for_tat_diverge_after_one_defection() {
+        let equivalence = compare(&Naive {}, &TitForTat {}, 8, &[], 0);
+        assert_eq!(
+            equivalence,
+            Equivalence::Divergent(Divergence {
+                history: vec![GamePlay {
+                    my_move: Move::Collaborate,
+                    their_move: Move::Defect,
+                }],
+                first_move: Move::Collaborate,
+                second_move: Move::Defect,
+            })
+        );
+    }
+
+    #[test]
+    fn tit_for_tat_and_grim_trigger_shortest_divergence() {
+        let equivalence = compare(&TitForTat {}, &GrimTrigger {}, 8, &[], 0);
+        match equivalence {
+            Equivalence::Divergent(divergence) => {
+                assert_eq!(divergence.history.len(), 2);
+                assert_eq!(divergence.first_move, Move::Collaborate);
+                assert_eq!(divergence.second_move, Move::Defect);
+            }
+            Equivalence::Identical => panic!("expected a divergence"),
+        }
+    }
+
+    #[test]
+    fn probes_find_divergence_beyond_depth() {
+        let probes: Vec<Box<dyn Strategy>> = vec![Box::new(Nasty {})];
+        let equivalence = compare(&Naive {}, &TitForTat {}, 0, &probes, 10);
+        match equivalence {
+            Equivalence::Divergent(divergence) => assert_eq!(divergence.history.len(), 1),
+            Equivalence::Identical => panic!("expected a divergence"),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let equivalence = compare(&Naive {}, &TitForTat {}, 8, &[], 0);
+        assert_eq!(
+            equivalence.to_string(),
+            "diverge after history [CD]: first plays C, second plays D"
+        );
+        assert_eq!(Equivalence::Identical.to_string(), "behaviorally identical");
+    }
+}
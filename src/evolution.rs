@@ -0,0 +1,186 @@
+use crate::player::Player;
+use crate::rng;
+use crate::strategy::Strategy;
+use crate::tournament::Tournament;
+use rayon::prelude::*;
+
+mod lattice;
+mod moran;
+mod replicator;
+mod wright_fisher;
+
+pub use lattice::{
+    evolve_lattice, Lattice, LatticeOutcome, LatticeSettings, Snapshot, UpdateMode, UpdateRule,
+};
+pub use moran::{fixation, moran_trajectory, FixationEstimate, MoranSettings};
+pub use replicator::{replicator_dynamics, ReplicatorOutcome, ReplicatorSettings};
+pub use wright_fisher::{wright_fisher, MutationKernel, WrightFisherOutcome, WrightFisherSettings};
+
+// Mean payoff per turn of every strategy against every other one, from which
+// the evolutionary dynamics derive fitness without playing any more matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoffMatrix {
+    pub strategies: Vec<String>,
+    // `payoffs[a][b]` is the mean payoff per turn of strategy `a` against
+    // strategy `b`, after complexity costs. The diagonal holds the payoffs
+    // against twins.
+    pub payoffs: Vec<Vec<f64>>,
+}
+
+impl PayoffMatrix {
+    // Plays every pair of strategies, every strategy against its twin too,
+    // once per repetition of the tournament, with its match settings.
+    pub fn new(strategies: &[Box<dyn Strategy>], tournament: &Tournament) -> Self {
+        let players: Vec<_> = strategies
+            .iter()
+            .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+            .collect();
+        let size = players.len();
+        let repetitions = tournament.repetitions();
+        let pairs: Vec<_> = (0..size)
+            .flat_map(|a| (a..size).map(move |b| (a, b)))
+            .flat_map(|pair| (0..repetitions).map(move |repetition| (repetition, pair)))
+            .collect();
+        let results: Vec<_> = pairs
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, (repetition, pair))| {
+                tournament.play_match(&players, index as u64, repetition, pair)
+            })
+            .collect();
+        let cost = tournament.complexity_cost();
+        let net_per_turn = |strategy: usize, score: u64, turns: u64| {
+            let turns = turns as f64;
+            (score as f64 - cost.per_match) / turns
+                - cost.per_turn(strategies[strategy].complexity())
+        };
+        let mut payoffs = vec![vec![0.0; size]; size];
+        for result in results {
+            let (a, b) = result.players;
+            payoffs[a][b] += net_per_turn(a, result.scores.0, result.turns);
+            if a != b {
+                payoffs[b][a] += net_per_turn(b, result.scores.1, result.turns);
+            }
+        }
+        for payoff in payoffs.iter_mut().flatten() {
+            *payoff /= f64::from(repetitions);
+        }
+        Self {
+            strategies: players.into_iter().map(|player| player.name).collect(),
+            payoffs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.strategies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strategies.is_empty()
+    }
+
+    // Mean payoff of an individual playing `strategy` against every other
+    // individual of a population with `counts[b]` individuals playing `b`.
+    pub fn payoff_in(&self, strategy: usize, counts: &[u32]) -> f64 {
+        let others = counts.iter().sum::<u32>().saturating_sub(1);
+        if others == 0 {
+            return 0.0;
+        }
+        let total: f64 = counts
+            .iter()
+            .zip(&self.payoffs[strategy])
+            .map(|(count, payoff)| f64::from(*count) * payoff)
+            .sum();
+        (total - self.payoffs[strategy][strategy]) / f64::from(others)
+    }
+}
+
+// Reproduction weight of every strategy: its count times its fitness
+// `exp(intensity * payoff)`. Fitnesses are scaled by that of the fittest
+// present strategy, so strong selection doesn't overflow them.
+fn fitness_weights(matrix: &PayoffMatrix, counts: &[u32], intensity: f64) -> Vec<f64> {
+    let exponents: Vec<f64> = (0..counts.len())
+        .map(|strategy| match counts[strategy] {
+            0 => f64::NEG_INFINITY,
+            _ => intensity * matrix.payoff_in(strategy, counts),
+        })
+        .collect();
+    let largest = exponents.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    counts
+        .iter()
+        .zip(&exponents)
+        .map(|(count, exponent)| match count {
+            0 => 0.0,
+            count => f64::from(*count) * (exponent - largest).exp(),
+        })
+        .collect()
+}
+
+// Index drawn with probability proportional to its weight.
+fn choose_weighted(weights: &[f64]) -> usize {
+    let mut target = rng::random_f64() * weights.iter().sum::<f64>();
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    weights
+        .iter()
+        .rposition(|weight| *weight > 0.0)
+        .expect("some positive weight")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::tournament::ComplexityCost;
+
+    fn strategies() -> Vec<Box<dyn Strategy>> {
+        vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+        ]
+    }
+
+    #[test]
+    fn payoffs_per_turn() {
+        let matrix = PayoffMatrix::new(&strategies(), &Tournament::all_pairs());
+        assert_eq!(matrix.strategies, vec!["Naive", "Nasty", "TitForTat"]);
+        assert_eq!(
+            matrix.payoffs,
+            vec![
+                vec![3.0, 0.0, 3.0],
+                vec![5.0, 1.0, 1.4],
+                vec![3.0, 0.9, 3.0]
+            ]
+        );
+    }
+
+    #[test]
+    fn complexity_costs_lower_payoffs() {
+        let tournament = Tournament::builder()
+            .complexity_cost(ComplexityCost {
+                per_memory_turn: 0.5,
+                per_state: 0.0,
+                per_match: 10.0,
+            })
+            .build()
+            .unwrap();
+        let matrix = PayoffMatrix::new(&strategies(), &tournament);
+        assert_eq!(matrix.payoffs[0][0], 2.0);
+        assert_eq!(matrix.payoffs[2][0], 1.5);
+    }
+
+    #[test]
+    fn payoff_against_the_rest_of_a_population() {
+        let matrix = PayoffMatrix::new(&strategies(), &Tournament::all_pairs());
+        assert_eq!(matrix.payoff_in(0, &[1, 2, 0]), 0.0);
+        assert_eq!(matrix.payoff_in(1, &[1, 2, 0]), 3.0);
+        assert_eq!(matrix.payoff_in(2, &[0, 0, 1]), 0.0);
+    }
+}
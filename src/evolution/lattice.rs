@@ -0,0 +1,357 @@
+use super::{choose_weighted, PayoffMatrix};
+use crate::graph::{Boundary, Graph, Neighborhood};
+use crate::rng;
+use rand::Rng;
+use std::fmt;
+
+// How a cell picks the strategy it plays next among its own and its
+// neighbors', after every cell played all of its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateRule {
+    // The strategy of the best scoring cell, keeping its own on a tie.
+    ImitateBest,
+    // A strategy drawn with probability proportional to the scores.
+    Proportional,
+    // The strategy of a random neighbor, adopted with probability
+    // `1 / (1 + exp((own - neighbor) / temperature))`.
+    Fermi { temperature: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateMode {
+    // Every cell updates at once from the same scores.
+    Synchronous,
+    // Cells drawn at random update one at a time from the current lattice,
+    // as many times per step as there are cells.
+    Asynchronous,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatticeSettings {
+    pub rule: UpdateRule,
+    pub mode: UpdateMode,
+    pub steps: u32,
+    // A snapshot is taken of the initial lattice and then every this many
+    // steps, or never again if 0.
+    pub snapshot_interval: u32,
+    pub seed: Option<u64>,
+}
+
+// Strategy of every cell of a `width` x `height` grid, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lattice {
+    width: usize,
+    graph: Graph,
+    cells: Vec<usize>,
+}
+
+// Strategy of every cell after a step, shown like the lattice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub step: u32,
+    pub width: usize,
+    pub cells: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatticeOutcome {
+    // Number of cells playing every strategy after every step, starting with
+    // the initial lattice.
+    pub counts: Vec<Vec<u32>>,
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl Lattice {
+    pub fn new(
+        width: usize,
+        cells: Vec<usize>,
+        neighborhood: Neighborhood,
+        boundary: Boundary,
+    ) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "the cells must fill whole rows"
+        );
+        let graph = Graph::grid(width, cells.len() / width, neighborhood, boundary);
+        Self {
+            width,
+            graph,
+            cells,
+        }
+    }
+
+    // Every cell playing one of `strategies` drawn uniformly at random.
+    pub fn random(
+        width: usize,
+        height: usize,
+        strategies: usize,
+        neighborhood: Neighborhood,
+        boundary: Boundary,
+    ) -> Self {
+        let cells = (0..width * height)
+            .map(|_| rng::with_rng(|rng| rng.gen_range(0..strategies)))
+            .collect();
+        Self::new(width, cells, neighborhood, boundary)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len() / self.width
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn snapshot(&self, step: u32) -> Snapshot {
+        Snapshot {
+            step,
+            width: self.width,
+            cells: self.cells.clone(),
+        }
+    }
+
+    pub fn counts(&self, strategies: usize) -> Vec<u32> {
+        let mut counts = vec![0; strategies];
+        for strategy in &self.cells {
+            counts[*strategy] += 1;
+        }
+        counts
+    }
+
+    // Sum of the payoffs per turn of a cell against all of its neighbors.
+    fn score(&self, matrix: &PayoffMatrix, cell: usize) -> f64 {
+        let payoffs = &matrix.payoffs[self.cells[cell]];
+        self.graph
+            .neighbors(cell)
+            .map(|neighbor| payoffs[self.cells[neighbor]])
+            .sum()
+    }
+
+    // Strategy of `cell` after an update by `rule`, given the score of every
+    // cell.
+    fn updated(&self, cell: usize, rule: UpdateRule, score: impl Fn(usize) -> f64) -> usize {
+        let neighbors: Vec<usize> = self.graph.neighbors(cell).collect();
+        match rule {
+            UpdateRule::ImitateBest => {
+                let mut best = cell;
+                for neighbor in neighbors {
+                    if score(neighbor) > score(best) {
+                        best = neighbor;
+                    }
+                }
+                self.cells[best]
+            }
+            UpdateRule::Proportional => {
+                let candidates: Vec<usize> = std::iter::once(cell).chain(neighbors).collect();
+                let weights: Vec<f64> = candidates
+                    .iter()
+                    .map(|candidate| score(*candidate).max(0.0))
+                    .collect();
+                if weights.iter().all(|weight| *weight == 0.0) {
+                    return self.cells[cell];
+                }
+                self.cells[candidates[choose_weighted(&weights)]]
+            }
+            UpdateRule::Fermi { temperature } => {
+                if neighbors.is_empty() {
+                    return self.cells[cell];
+                }
+                let neighbor = neighbors[rng::with_rng(|rng| rng.gen_range(0..neighbors.len()))];
+                let adoption = 1.0 / (1.0 + ((score(cell) - score(neighbor)) / temperature).exp());
+                if rng::random_bool(adoption) {
+                    self.cells[neighbor]
+                } else {
+                    self.cells[cell]
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, matrix: &PayoffMatrix, settings: &LatticeSettings) {
+        match settings.mode {
+            UpdateMode::Synchronous => {
+                let scores: Vec<f64> = (0..self.cells.len())
+                    .map(|cell| self.score(matrix, cell))
+                    .collect();
+                self.cells = (0..self.cells.len())
+                    .map(|cell| self.updated(cell, settings.rule, |other| scores[other]))
+                    .collect();
+            }
+            UpdateMode::Asynchronous => {
+                for _ in 0..self.cells.len() {
+                    let cell = rng::with_rng(|rng| rng.gen_range(0..self.cells.len()));
+                    self.cells[cell] =
+                        self.updated(cell, settings.rule, |other| self.score(matrix, other));
+                }
+            }
+        }
+    }
+}
+
+// One character per cell: the index of its strategy in base 36.
+fn write_cells(f: &mut fmt::Formatter, width: usize, cells: &[usize]) -> fmt::Result {
+    for (y, row) in cells.chunks(width).enumerate() {
+        if y > 0 {
+            writeln!(f)?;
+        }
+        for strategy in row {
+            let symbol = char::from_digit(*strategy as u32, 36).unwrap_or('?');
+            write!(f, "{}", symbol)?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Lattice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_cells(f, self.width, &self.cells)
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_cells(f, self.width, &self.cells)
+    }
+}
+
+// Spatial game of Nowak and May: every step each cell plays all of its
+// neighbors, scoring the payoffs of the matrix, and then updates its
+// strategy by the rule.
+pub fn evolve_lattice(
+    matrix: &PayoffMatrix,
+    mut lattice: Lattice,
+    settings: &LatticeSettings,
+) -> LatticeOutcome {
+    assert!(
+        lattice
+            .cells
+            .iter()
+            .all(|strategy| *strategy < matrix.len()),
+        "every cell must play a strategy of the matrix"
+    );
+    if let UpdateRule::Fermi { temperature } = settings.rule {
+        assert!(temperature > 0.0, "the temperature must be positive");
+    }
+    if let Some(seed) = settings.seed {
+        rng::seed(seed);
+    }
+    let mut counts = vec![lattice.counts(matrix.len())];
+    let mut snapshots = vec![lattice.snapshot(0)];
+    for step in 1..=settings.steps {
+        lattice.step(matrix, settings);
+        counts.push(lattice.counts(matrix.len()));
+        if settings.snapshot_interval > 0 && step.is_multiple_of(settings.snapshot_interval) {
+            snapshots.push(lattice.snapshot(step));
+        }
+    }
+    LatticeOutcome { counts, snapshots }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::Strategy;
+    use crate::tournament::Tournament;
+
+    fn matrix() -> PayoffMatrix {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        PayoffMatrix::new(&strategies, &Tournament::all_pairs())
+    }
+
+    fn settings(rule: UpdateRule, mode: UpdateMode) -> LatticeSettings {
+        LatticeSettings {
+            rule,
+            mode,
+            steps: 4,
+            snapshot_interval: 2,
+            seed: Some(1),
+        }
+    }
+
+    fn lone_defector(boundary: Boundary) -> Lattice {
+        let mut cells = vec![0; 25];
+        cells[12] = 1;
+        Lattice::new(5, cells, Neighborhood::Moore, boundary)
+    }
+
+    #[test]
+    fn imitate_best_spreads_a_lone_defector() {
+        let settings = settings(UpdateRule::ImitateBest, UpdateMode::Synchronous);
+        let outcome = evolve_lattice(&matrix(), lone_defector(Boundary::Periodic), &settings);
+        assert_eq!(outcome.counts[0], vec![24, 1]);
+        // The defector scores 8 * 5 against at most 7 * 3 for its neighbors.
+        assert_eq!(outcome.counts[1], vec![16, 9]);
+        assert_eq!(
+            outcome.snapshots[0].to_string(),
+            "00000\n00000\n00100\n00000\n00000"
+        );
+        let steps: Vec<_> = outcome
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.step)
+            .collect();
+        assert_eq!(steps, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn fixed_boundaries_leave_edges_fewer_neighbors() {
+        let mut cells = vec![0; 9];
+        cells[0] = 1;
+        let lattice = Lattice::new(3, cells, Neighborhood::VonNeumann, Boundary::Fixed);
+        assert_eq!(lattice.score(&matrix(), 0), 10.0);
+        assert_eq!(lattice.score(&matrix(), 4), 12.0);
+        assert_eq!((lattice.width(), lattice.height()), (3, 3));
+    }
+
+    #[test]
+    fn stochastic_rules_keep_the_lattice_size() {
+        for rule in [
+            UpdateRule::Proportional,
+            UpdateRule::Fermi { temperature: 0.5 },
+        ] {
+            for mode in [UpdateMode::Synchronous, UpdateMode::Asynchronous] {
+                let settings = settings(rule, mode);
+                let outcome = evolve_lattice(&matrix(), lone_defector(Boundary::Fixed), &settings);
+                assert_eq!(outcome.counts.len(), 5);
+                assert!(outcome
+                    .counts
+                    .iter()
+                    .all(|counts| counts[0] + counts[1] == 25));
+                let again = evolve_lattice(&matrix(), lone_defector(Boundary::Fixed), &settings);
+                assert_eq!(again, outcome);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "temperature must be positive")]
+    fn rejects_frozen_fermi_rule() {
+        let settings = settings(
+            UpdateRule::Fermi { temperature: 0.0 },
+            UpdateMode::Synchronous,
+        );
+        evolve_lattice(&matrix(), lone_defector(Boundary::Fixed), &settings);
+    }
+
+    #[test]
+    #[should_panic(expected = "strategy of the matrix")]
+    fn rejects_cells_of_other_strategies() {
+        let lattice = Lattice::new(2, vec![0, 1, 2, 0], Neighborhood::Moore, Boundary::Fixed);
+        let settings = settings(UpdateRule::ImitateBest, UpdateMode::Synchronous);
+        evolve_lattice(&matrix(), lattice, &settings);
+    }
+
+    #[test]
+    fn random_lattice() {
+        rng::seed(5);
+        let lattice = Lattice::random(4, 3, 2, Neighborhood::Moore, Boundary::Periodic);
+        assert_eq!(lattice.cells().len(), 12);
+        assert_eq!(lattice.counts(2).iter().sum::<u32>(), 12);
+    }
+}
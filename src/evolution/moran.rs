@@ -0,0 +1,275 @@
+use super::{choose_weighted, fitness_weights, PayoffMatrix};
+use crate::rng;
+use rand::Rng;
+use rayon::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoranSettings {
+    // Fitness is `exp(intensity * payoff)`, so selection is neutral at 0 and
+    // grows stronger with the intensity.
+    pub intensity: f64,
+    // Probability of an offspring playing a strategy drawn uniformly at
+    // random instead of its parent's.
+    pub mutation: f64,
+    pub seed: Option<u64>,
+}
+
+impl Default for MoranSettings {
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            mutation: 0.0,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixationEstimate {
+    pub runs: u32,
+    // Share of the runs in which the mutant took over the population.
+    pub probability: f64,
+    // Mean number of steps until the mutant took over, over the runs in which
+    // it did. None if it never did.
+    pub mean_time: Option<f64>,
+}
+
+// Population counts of every strategy after every step of a Moran process,
+// starting with `initial`. Without mutation the process stops once a single
+// strategy is left, as nothing can change anymore.
+pub fn moran_trajectory(
+    matrix: &PayoffMatrix,
+    initial: &[u32],
+    settings: &MoranSettings,
+    steps: u64,
+) -> Vec<Vec<u32>> {
+    validate(settings);
+    assert_eq!(
+        initial.len(),
+        matrix.len(),
+        "every strategy of the matrix needs a count"
+    );
+    assert!(
+        initial.iter().any(|count| *count > 0),
+        "the population can't be empty"
+    );
+    if let Some(seed) = settings.seed {
+        rng::seed(seed);
+    }
+    let mut counts = initial.to_vec();
+    let mut trajectory = vec![counts.clone()];
+    for _ in 0..steps {
+        if settings.mutation == 0.0 && is_fixed(&counts) {
+            break;
+        }
+        moran_step(matrix, &mut counts, settings);
+        trajectory.push(counts.clone());
+    }
+    trajectory
+}
+
+// Monte Carlo estimate of the fixation of a single `mutant` in a population
+// of `population - 1` individuals playing `resident`, over `runs` runs of a
+// Moran process without mutation. In a seeded estimate every run has its own
+// seed, so the runs can be played in parallel.
+pub fn fixation(
+    matrix: &PayoffMatrix,
+    mutant: usize,
+    resident: usize,
+    population: u32,
+    settings: &MoranSettings,
+    runs: u32,
+) -> FixationEstimate {
+    validate(settings);
+    assert!(runs > 0, "the estimate needs at least one run");
+    assert!(population >= 2, "a population needs at least two players");
+    assert!(
+        mutant < matrix.len() && resident < matrix.len(),
+        "the mutant and the resident must be strategies of the matrix"
+    );
+    assert_ne!(mutant, resident, "the mutant must differ from the resident");
+    let settings = MoranSettings {
+        mutation: 0.0,
+        ..*settings
+    };
+    let fixation_times: Vec<Option<u64>> = (0..runs)
+        .into_par_iter()
+        .map(|run| {
+            if let Some(seed) = settings.seed {
+                rng::seed(rng::derive_seed(seed, u64::from(run)));
+            }
+            let mut counts = vec![0; matrix.len()];
+            counts[resident] += population - 1;
+            counts[mutant] += 1;
+            let mut steps = 0;
+            while counts[mutant] > 0 && counts[mutant] < population {
+                moran_step(matrix, &mut counts, &settings);
+                steps += 1;
+            }
+            (counts[mutant] == population).then_some(steps)
+        })
+        .collect();
+    let times: Vec<u64> = fixation_times.into_iter().flatten().collect();
+    FixationEstimate {
+        runs,
+        probability: times.len() as f64 / f64::from(runs),
+        mean_time: (!times.is_empty())
+            .then(|| times.iter().sum::<u64>() as f64 / times.len() as f64),
+    }
+}
+
+fn validate(settings: &MoranSettings) {
+    assert!(
+        settings.intensity >= 0.0,
+        "the intensity of selection can't be negative"
+    );
+    assert!(
+        (0.0..=1.0).contains(&settings.mutation),
+        "the mutation rate must be a probability"
+    );
+}
+
+fn is_fixed(counts: &[u32]) -> bool {
+    counts.iter().filter(|count| **count > 0).count() <= 1
+}
+
+// One birth-death step: an individual chosen with probability proportional
+// to its fitness reproduces, and its offspring replaces an individual chosen
+// uniformly at random, possibly the parent.
+fn moran_step(matrix: &PayoffMatrix, counts: &mut [u32], settings: &MoranSettings) {
+    let weights = fitness_weights(matrix, counts, settings.intensity);
+    let parent = choose_weighted(&weights);
+    let offspring = if settings.mutation > 0.0 && rng::random_bool(settings.mutation) {
+        rng::with_rng(|rng| rng.gen_range(0..counts.len()))
+    } else {
+        parent
+    };
+    let sizes: Vec<f64> = counts.iter().map(|count| f64::from(*count)).collect();
+    let replaced = choose_weighted(&sizes);
+    counts[replaced] -= 1;
+    counts[offspring] += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::Strategy;
+    use crate::tournament::Tournament;
+
+    fn matrix() -> PayoffMatrix {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        PayoffMatrix::new(&strategies, &Tournament::all_pairs())
+    }
+
+    #[test]
+    fn trajectory_keeps_population_size() {
+        let settings = MoranSettings {
+            mutation: 0.1,
+            seed: Some(1),
+            ..MoranSettings::default()
+        };
+        let trajectory = moran_trajectory(&matrix(), &[5, 5], &settings, 100);
+        assert_eq!(trajectory.len(), 101);
+        assert_eq!(trajectory[0], vec![5, 5]);
+        assert!(trajectory.iter().all(|counts| counts[0] + counts[1] == 10));
+        assert_eq!(
+            moran_trajectory(&matrix(), &[5, 5], &settings, 100),
+            trajectory
+        );
+    }
+
+    #[test]
+    fn trajectory_stops_at_fixation() {
+        let settings = MoranSettings {
+            seed: Some(2),
+            ..MoranSettings::default()
+        };
+        let trajectory = moran_trajectory(&matrix(), &[3, 1], &settings, 10_000);
+        let last = trajectory.last().unwrap();
+        assert!(last.contains(&0));
+        assert!(trajectory[..trajectory.len() - 1]
+            .iter()
+            .all(|counts| !counts.contains(&0)));
+    }
+
+    #[test]
+    fn neutral_fixation_is_one_over_population() {
+        let settings = MoranSettings {
+            intensity: 0.0,
+            seed: Some(3),
+            ..MoranSettings::default()
+        };
+        let estimate = fixation(&matrix(), 1, 0, 10, &settings, 4000);
+        assert_eq!(estimate.runs, 4000);
+        assert!((estimate.probability - 0.1).abs() < 0.02);
+        assert!(estimate.mean_time.unwrap() > 10.0);
+        assert_eq!(fixation(&matrix(), 1, 0, 10, &settings, 4000), estimate);
+    }
+
+    #[test]
+    fn defectors_invade_cooperators() {
+        let settings = MoranSettings {
+            seed: Some(4),
+            ..MoranSettings::default()
+        };
+        assert!(fixation(&matrix(), 1, 0, 10, &settings, 500).probability > 0.5);
+        assert_eq!(
+            fixation(&matrix(), 0, 1, 10, &settings, 500).mean_time,
+            None
+        );
+    }
+
+    #[test]
+    fn strong_selection_doesnt_overflow() {
+        let settings = MoranSettings {
+            intensity: 1000.0,
+            seed: Some(5),
+            ..MoranSettings::default()
+        };
+        // Nasty is always the parent, so Naive can never spread.
+        let estimate = fixation(&matrix(), 1, 0, 10, &settings, 200);
+        assert!(estimate.probability > 0.5);
+        assert_eq!(
+            fixation(&matrix(), 0, 1, 10, &settings, 200).probability,
+            0.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "a probability")]
+    fn rejects_invalid_mutation() {
+        let settings = MoranSettings {
+            mutation: 1.5,
+            ..MoranSettings::default()
+        };
+        moran_trajectory(&matrix(), &[5, 5], &settings, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "intensity of selection")]
+    fn rejects_invalid_intensity() {
+        let settings = MoranSettings {
+            intensity: f64::NAN,
+            ..MoranSettings::default()
+        };
+        fixation(&matrix(), 1, 0, 10, &settings, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be empty")]
+    fn rejects_empty_population() {
+        let settings = MoranSettings {
+            mutation: 0.1,
+            ..MoranSettings::default()
+        };
+        moran_trajectory(&matrix(), &[0, 0], &settings, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a count")]
+    fn rejects_counts_of_other_strategies() {
+        moran_trajectory(&matrix(), &[5], &MoranSettings::default(), 10);
+    }
+}
@@ -0,0 +1,184 @@
+use super::PayoffMatrix;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplicatorSettings {
+    // Most generations iterated.
+    pub generations: u32,
+    // The shares have converged once no share moves more than this in a
+    // generation.
+    pub tolerance: f64,
+}
+
+impl Default for ReplicatorSettings {
+    fn default() -> Self {
+        Self {
+            generations: 1000,
+            tolerance: 1e-9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicatorOutcome {
+    // Population share of every strategy in every generation, starting with
+    // the initial shares.
+    pub trajectory: Vec<Vec<f64>>,
+    // Generation in which the shares converged, if they did. None too if
+    // every strategy earned nothing against the mix, which leaves the next
+    // shares undefined and ends the trajectory.
+    pub converged_at: Option<u32>,
+}
+
+impl ReplicatorOutcome {
+    pub fn final_shares(&self) -> &[f64] {
+        self.trajectory.last().expect("initial shares")
+    }
+}
+
+// Discrete replicator dynamics, as in Axelrod's ecological tournament: every
+// generation the share of a strategy is multiplied by its payoff against the
+// current mix, relative to the mean payoff. Negative payoffs, left by
+// complexity costs, count as zero. The initial shares needn't sum to one.
+pub fn replicator_dynamics(
+    matrix: &PayoffMatrix,
+    initial: &[f64],
+    settings: &ReplicatorSettings,
+) -> ReplicatorOutcome {
+    assert_eq!(
+        initial.len(),
+        matrix.len(),
+        "every strategy of the matrix needs a share"
+    );
+    assert!(
+        initial.iter().all(|share| *share >= 0.0),
+        "the shares can't be negative"
+    );
+    let total: f64 = initial.iter().sum();
+    assert!(total > 0.0, "some strategy needs a positive share");
+    let mut shares: Vec<f64> = initial.iter().map(|share| share / total).collect();
+    let mut trajectory = vec![shares.clone()];
+    for generation in 1..=settings.generations {
+        let fitness: Vec<f64> = (0..shares.len())
+            .map(|strategy| {
+                shares
+                    .iter()
+                    .zip(&matrix.payoffs[strategy])
+                    .map(|(share, payoff)| share * payoff)
+                    .sum::<f64>()
+                    .max(0.0)
+            })
+            .collect();
+        let mean: f64 = shares.iter().zip(&fitness).map(|(s, f)| s * f).sum();
+        if mean == 0.0 {
+            return ReplicatorOutcome {
+                trajectory,
+                converged_at: None,
+            };
+        }
+        let next: Vec<f64> = shares
+            .iter()
+            .zip(&fitness)
+            .map(|(share, fitness)| share * fitness / mean)
+            .collect();
+        let change = shares
+            .iter()
+            .zip(&next)
+            .map(|(share, next)| (share - next).abs())
+            .fold(0.0, f64::max);
+        shares = next;
+        trajectory.push(shares.clone());
+        if change <= settings.tolerance {
+            return ReplicatorOutcome {
+                trajectory,
+                converged_at: Some(generation),
+            };
+        }
+    }
+    ReplicatorOutcome {
+        trajectory,
+        converged_at: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::strategy::Strategy;
+    use crate::tournament::{ComplexityCost, Tournament};
+
+    fn matrix(strategies: Vec<Box<dyn Strategy>>) -> PayoffMatrix {
+        PayoffMatrix::new(&strategies, &Tournament::all_pairs())
+    }
+
+    #[test]
+    fn defectors_take_over_cooperators() {
+        let matrix = matrix(vec![Box::new(Naive {}), Box::new(Nasty {})]);
+        let outcome = replicator_dynamics(&matrix, &[9.0, 1.0], &ReplicatorSettings::default());
+        assert_eq!(outcome.trajectory[0], vec![0.9, 0.1]);
+        // Nasty earns 5 * 0.9 + 0.1 against 2.7 for Naive.
+        assert!((outcome.trajectory[1][1] - 0.1 * 4.6 / 2.89).abs() < 1e-12);
+        assert!(outcome.converged_at.is_some());
+        assert!(outcome.final_shares()[1] > 0.999);
+        assert!(outcome
+            .trajectory
+            .iter()
+            .all(|shares| (shares.iter().sum::<f64>() - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn equal_payoffs_converge_at_once() {
+        let matrix = matrix(vec![Box::new(Naive {}), Box::new(TitForTat {})]);
+        let outcome = replicator_dynamics(&matrix, &[1.0, 3.0], &ReplicatorSettings::default());
+        assert_eq!(outcome.converged_at, Some(1));
+        assert_eq!(outcome.trajectory, vec![vec![0.25, 0.75]; 2]);
+    }
+
+    #[test]
+    fn stops_after_the_last_generation() {
+        let matrix = matrix(vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+        ]);
+        let settings = ReplicatorSettings {
+            generations: 5,
+            tolerance: 0.0,
+        };
+        let outcome = replicator_dynamics(&matrix, &[1.0, 1.0, 1.0], &settings);
+        assert_eq!(outcome.trajectory.len(), 6);
+        assert_eq!(outcome.converged_at, None);
+    }
+
+    #[test]
+    fn worthless_payoffs_dont_converge() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let tournament = Tournament::builder()
+            .complexity_cost(ComplexityCost {
+                per_match: 100.0,
+                ..ComplexityCost::default()
+            })
+            .build()
+            .unwrap();
+        let matrix = PayoffMatrix::new(&strategies, &tournament);
+        let outcome = replicator_dynamics(&matrix, &[1.0, 1.0], &ReplicatorSettings::default());
+        assert_eq!(outcome.converged_at, None);
+        assert_eq!(outcome.trajectory, vec![vec![0.5, 0.5]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be negative")]
+    fn rejects_negative_shares() {
+        let matrix = matrix(vec![Box::new(Naive {}), Box::new(Nasty {})]);
+        replicator_dynamics(&matrix, &[2.0, -1.0], &ReplicatorSettings::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a share")]
+    fn rejects_shares_of_other_strategies() {
+        let matrix = matrix(vec![Box::new(Naive {}), Box::new(Nasty {})]);
+        replicator_dynamics(&matrix, &[1.0], &ReplicatorSettings::default());
+    }
+}
@@ -0,0 +1,258 @@
+use super::{choose_weighted, fitness_weights, PayoffMatrix};
+use crate::rng;
+
+// Probabilities of the offspring of every strategy playing every strategy:
+// `rows[a][b]` for an offspring of `a` playing `b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationKernel {
+    rows: Vec<Vec<f64>>,
+}
+
+impl MutationKernel {
+    pub fn new(rows: Vec<Vec<f64>>) -> Self {
+        for row in &rows {
+            assert_eq!(row.len(), rows.len(), "the kernel must be square");
+            assert!(
+                row.iter().all(|probability| *probability >= 0.0),
+                "the kernel can't have negative probabilities"
+            );
+            assert!(
+                (row.iter().sum::<f64>() - 1.0).abs() < 1e-9,
+                "every row must sum to one"
+            );
+        }
+        Self { rows }
+    }
+
+    // Offspring always play their parent's strategy.
+    pub fn identity(strategies: usize) -> Self {
+        Self::uniform(strategies, 0.0)
+    }
+
+    // Offspring play any other strategy with probability `rate`, all other
+    // strategies being equally likely. A single strategy has nothing to
+    // mutate into.
+    pub fn uniform(strategies: usize, rate: f64) -> Self {
+        let rate = if strategies > 1 { rate } else { 0.0 };
+        let others = strategies.saturating_sub(1).max(1) as f64;
+        Self::new(
+            (0..strategies)
+                .map(|a| {
+                    (0..strategies)
+                        .map(|b| if a == b { 1.0 - rate } else { rate / others })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrightFisherSettings {
+    // Fitness is `exp(intensity * payoff)`, as in the Moran process.
+    pub intensity: f64,
+    pub mutation: MutationKernel,
+    pub generations: u32,
+    // Generations left out of the time-averaged abundance, while the
+    // population forgets its initial composition.
+    pub burn_in: u32,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrightFisherOutcome {
+    // Frequency of every strategy in every generation, starting with the
+    // initial one.
+    pub trajectory: Vec<Vec<f64>>,
+    // Mean frequency of every strategy over the generations after the
+    // burn-in.
+    pub mean_abundance: Vec<f64>,
+}
+
+// Generational Wright-Fisher process: every generation replaces the whole
+// population, each offspring having a parent drawn with probability
+// proportional to fitness and then mutating by the kernel.
+pub fn wright_fisher(
+    matrix: &PayoffMatrix,
+    initial: &[u32],
+    settings: &WrightFisherSettings,
+) -> WrightFisherOutcome {
+    assert!(
+        initial.len() == matrix.len() && settings.mutation.rows.len() == matrix.len(),
+        "the counts and the kernel must cover every strategy of the matrix"
+    );
+    assert!(
+        settings.intensity >= 0.0,
+        "the intensity of selection can't be negative"
+    );
+    assert!(
+        settings.generations > settings.burn_in,
+        "some generation must come after the burn-in"
+    );
+    rng::seed(settings.seed);
+    let population: u32 = initial.iter().sum();
+    assert!(population > 0, "the population can't be empty");
+    let frequencies = |counts: &[u32]| -> Vec<f64> {
+        counts
+            .iter()
+            .map(|count| f64::from(*count) / f64::from(population))
+            .collect()
+    };
+    let mut counts = initial.to_vec();
+    let mut trajectory = vec![frequencies(&counts)];
+    let mut abundance = vec![0.0; counts.len()];
+    for generation in 1..=settings.generations {
+        let parents = fitness_weights(matrix, &counts, settings.intensity);
+        let offspring: Vec<f64> = (0..counts.len())
+            .map(|b| {
+                parents
+                    .iter()
+                    .zip(&settings.mutation.rows)
+                    .map(|(parent, row)| parent * row[b])
+                    .sum()
+            })
+            .collect();
+        counts = multinomial(population, &offspring);
+        let current = frequencies(&counts);
+        if generation > settings.burn_in {
+            for (total, frequency) in abundance.iter_mut().zip(&current) {
+                *total += frequency;
+            }
+        }
+        trajectory.push(current);
+    }
+    let averaged = settings.generations - settings.burn_in;
+    for total in abundance.iter_mut() {
+        *total /= f64::from(averaged);
+    }
+    WrightFisherOutcome {
+        trajectory,
+        mean_abundance: abundance,
+    }
+}
+
+// Counts of `draws` independent draws with probabilities proportional to
+// the weights.
+fn multinomial(draws: u32, weights: &[f64]) -> Vec<u32> {
+    let mut counts = vec![0; weights.len()];
+    for _ in 0..draws {
+        counts[choose_weighted(weights)] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::Strategy;
+    use crate::tournament::Tournament;
+
+    fn matrix() -> PayoffMatrix {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        PayoffMatrix::new(&strategies, &Tournament::all_pairs())
+    }
+
+    fn settings(rate: f64, seed: u64) -> WrightFisherSettings {
+        WrightFisherSettings {
+            intensity: 1.0,
+            mutation: MutationKernel::uniform(2, rate),
+            generations: 200,
+            burn_in: 0,
+            seed,
+        }
+    }
+
+    #[test]
+    fn uniform_kernel() {
+        assert_eq!(
+            MutationKernel::uniform(3, 0.2),
+            MutationKernel::new(vec![
+                vec![0.8, 0.1, 0.1],
+                vec![0.1, 0.8, 0.1],
+                vec![0.1, 0.1, 0.8]
+            ])
+        );
+        assert_eq!(
+            MutationKernel::identity(2),
+            MutationKernel::new(vec![vec![1.0, 0.0], vec![0.0, 1.0]])
+        );
+        assert_eq!(MutationKernel::uniform(1, 0.3), MutationKernel::identity(1));
+    }
+
+    #[test]
+    fn strong_selection_doesnt_overflow() {
+        let mut settings = settings(0.0, 5);
+        settings.intensity = 1000.0;
+        settings.generations = 20;
+        let outcome = wright_fisher(&matrix(), &[15, 5], &settings);
+        assert_eq!(outcome.trajectory[1], vec![0.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "intensity of selection")]
+    fn rejects_invalid_intensity() {
+        let mut settings = settings(0.1, 1);
+        settings.intensity = -1.0;
+        wright_fisher(&matrix(), &[10, 10], &settings);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative probabilities")]
+    fn rejects_negative_probabilities() {
+        MutationKernel::new(vec![vec![1.5, -0.5], vec![0.0, 1.0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "every strategy of the matrix")]
+    fn rejects_kernel_of_other_strategies() {
+        let mut settings = settings(0.1, 1);
+        settings.mutation = MutationKernel::uniform(3, 0.1);
+        wright_fisher(&matrix(), &[10, 10], &settings);
+    }
+
+    #[test]
+    #[should_panic(expected = "after the burn-in")]
+    fn rejects_burn_in_of_every_generation() {
+        let mut settings = settings(0.1, 1);
+        settings.burn_in = settings.generations;
+        wright_fisher(&matrix(), &[10, 10], &settings);
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let outcome = wright_fisher(&matrix(), &[10, 10], &settings(0.05, 1));
+        assert_eq!(outcome.trajectory.len(), 201);
+        assert_eq!(outcome.trajectory[0], vec![0.5, 0.5]);
+        assert!(outcome
+            .trajectory
+            .iter()
+            .all(|frequencies| (frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-9));
+        assert_eq!(
+            wright_fisher(&matrix(), &[10, 10], &settings(0.05, 1)),
+            outcome
+        );
+        assert_ne!(
+            wright_fisher(&matrix(), &[10, 10], &settings(0.05, 2)),
+            outcome
+        );
+    }
+
+    #[test]
+    fn defectors_take_over_without_mutation() {
+        let outcome = wright_fisher(&matrix(), &[15, 5], &settings(0.0, 3));
+        assert_eq!(outcome.trajectory.last().unwrap(), &vec![0.0, 1.0]);
+        assert!(outcome.mean_abundance[1] > 0.9);
+    }
+
+    #[test]
+    fn full_mutation_mixes_evenly() {
+        let mut settings = settings(0.5, 4);
+        settings.generations = 2000;
+        settings.burn_in = 100;
+        let outcome = wright_fisher(&matrix(), &[20, 0], &settings);
+        assert!((outcome.mean_abundance[0] - 0.5).abs() < 0.02);
+        assert!((outcome.mean_abundance.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+}
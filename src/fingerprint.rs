@@ -0,0 +1,210 @@
+use crate::game::{Move, Payoff};
+use crate::player::{self, GamePlay, Player};
+use crate::rng;
+use crate::strategy::Strategy;
+use std::fmt;
+
+// Ashlock's "Joss-Ann" probe built on top of TitForTat. For x + y <= 1 the
+// probe collaborates with probability x, defects with probability y and plays
+// TitForTat otherwise. For x + y > 1 it switches to the dual form: collaborate
+// with probability 1 - y, defect with probability 1 - x and play the opposite
+// of TitForTat otherwise. This keeps the fingerprint continuous over the
+// whole unit square.
+pub struct TitForTatProbe {
+    x: f64,
+    y: f64,
+}
+
+impl TitForTatProbe {
+    pub fn new(x: f64, y: f64) -> Self {
+        assert!((0.0..=1.0).contains(&x), "x must be in [0, 1]");
+        assert!((0.0..=1.0).contains(&y), "y must be in [0, 1]");
+        Self { x, y }
+    }
+
+    fn tit_for_tat(past_games: &[GamePlay]) -> Move {
+        match past_games.last() {
+            None => Move::Collaborate,
+            Some(last_game) => last_game.their_move,
+        }
+    }
+}
+
+impl Strategy for TitForTatProbe {
+    fn play(&self, past_games: &[GamePlay]) -> Move {
+        let dual = self.x + self.y > 1.0;
+        let (collaborate, defect) = if dual {
+            (1.0 - self.y, 1.0 - self.x)
+        } else {
+            (self.x, self.y)
+        };
+        let draw = rng::random_f64();
+        if draw < collaborate {
+            Move::Collaborate
+        } else if draw < collaborate + defect {
+            Move::Defect
+        } else if dual {
+            Self::tit_for_tat(past_games).oposite_move()
+        } else {
+            Self::tit_for_tat(past_games)
+        }
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+// Expected score per turn of a strategy against `TitForTatProbe` sampled on a
+// regular grid. `values[i][j]` holds the score for x = i / resolution and
+// y = j / resolution.
+pub struct Fingerprint {
+    pub strategy: String,
+    pub resolution: usize,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl Fingerprint {
+    pub fn value(&self, x_index: usize, y_index: usize) -> f64 {
+        self.values[x_index][y_index]
+    }
+
+    // Mean absolute difference between two fingerprints of the same
+    // resolution. Zero means the strategies can't be told apart by the probe.
+    pub fn distance(&self, other: &Fingerprint) -> f64 {
+        assert_eq!(
+            self.resolution, other.resolution,
+            "fingerprints have different resolutions"
+        );
+        let points = (self.resolution + 1) * (self.resolution + 1);
+        let total: f64 = self
+            .values
+            .iter()
+            .flatten()
+            .zip(other.values.iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        total / points as f64
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.strategy)?;
+        write!(f, "  y\\x")?;
+        for i in 0..=self.resolution {
+            write!(f, " {:5.2}", i as f64 / self.resolution as f64)?;
+        }
+        writeln!(f)?;
+        for j in (0..=self.resolution).rev() {
+            write!(f, "{:5.2}", j as f64 / self.resolution as f64)?;
+            for i in 0..=self.resolution {
+                write!(f, " {:5.2}", self.value(i, j))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn fingerprint(
+    strategy: &dyn Strategy,
+    payoff: &Payoff,
+    resolution: usize,
+    turns: u32,
+    repetitions: u32,
+) -> Fingerprint {
+    assert!(resolution > 0, "resolution must be positive");
+    assert!(turns > 0, "turns must be positive");
+    assert!(repetitions > 0, "repetitions must be positive");
+    let values = (0..=resolution)
+        .map(|i| {
+            (0..=resolution)
+                .map(|j| {
+                    let probe = TitForTatProbe::new(
+                        i as f64 / resolution as f64,
+                        j as f64 / resolution as f64,
+                    );
+                    expected_score(strategy, &probe, payoff, turns, repetitions)
+                })
+                .collect()
+        })
+        .collect();
+    Fingerprint {
+        strategy: strategy.name(),
+        resolution,
+        values,
+    }
+}
+
+fn expected_score(
+    strategy: &dyn Strategy,
+    probe: &TitForTatProbe,
+    payoff: &Payoff,
+    turns: u32,
+    repetitions: u32,
+) -> f64 {
+    let total: u64 = (0..repetitions)
+        .map(|_| {
+            let mut player = Player::new("player", strategy);
+            let mut probe_player = Player::new("probe", probe);
+            player::play_games(&mut player, &mut probe_player, payoff, turns);
+            player.score()
+        })
+        .sum();
+    total as f64 / (f64::from(turns) * f64::from(repetitions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+
+    const TEST_PAYOFF: Payoff = Payoff::new(5, 3, 1, 0);
+
+    #[test]
+    fn probe_corners_are_deterministic() {
+        let history = [GamePlay {
+            my_move: Move::Collaborate,
+            their_move: Move::Defect,
+        }];
+        assert_eq!(TitForTatProbe::new(0.0, 0.0).play(&history), Move::Defect);
+        assert_eq!(
+            TitForTatProbe::new(1.0, 0.0).play(&history),
+            Move::Collaborate
+        );
+        assert_eq!(TitForTatProbe::new(0.0, 1.0).play(&history), Move::Defect);
+        assert_eq!(
+            TitForTatProbe::new(1.0, 1.0).play(&history),
+            Move::Collaborate
+        );
+    }
+
+    #[test]
+    fn naive_fingerprint_corners() {
+        let fingerprint = fingerprint(&Naive {}, &TEST_PAYOFF, 4, 10, 2);
+        assert_eq!(fingerprint.strategy, "Naive");
+        assert_eq!(fingerprint.values.len(), 5);
+        assert_eq!(fingerprint.value(0, 0), 3.0);
+        assert_eq!(fingerprint.value(4, 0), 3.0);
+        assert_eq!(fingerprint.value(0, 4), 0.0);
+    }
+
+    #[test]
+    fn nasty_fingerprint_corners() {
+        let fingerprint = fingerprint(&Nasty {}, &TEST_PAYOFF, 4, 10, 2);
+        assert_eq!(fingerprint.value(0, 0), 1.4);
+        assert_eq!(fingerprint.value(4, 0), 5.0);
+        assert_eq!(fingerprint.value(0, 4), 1.0);
+    }
+
+    #[test]
+    fn distance() {
+        let naive = fingerprint(&Naive {}, &TEST_PAYOFF, 2, 10, 2);
+        let nasty = fingerprint(&Nasty {}, &TEST_PAYOFF, 2, 10, 2);
+        assert_eq!(naive.distance(&naive), 0.0);
+        assert!(naive.distance(&nasty) > 0.0);
+        assert_eq!(naive.distance(&nasty), nasty.distance(&naive));
+    }
+}
@@ -1,4 +1,7 @@
-#[derive(PartialEq, Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Move {
     Collaborate,
     Defect,
@@ -13,6 +16,28 @@ impl Move {
     }
 }
 
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Move::Collaborate => write!(f, "C"),
+            Move::Defect => write!(f, "D"),
+        }
+    }
+}
+
+// Whether both players move at once or the second one sees the first one's
+// move before making its own.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Timing {
+    #[default]
+    Simultaneous,
+    // Nowak & Sigmund's alternating game: every round the first player moves,
+    // then the second player answers, and the pair of moves is paid as usual.
+    // Tournaments swap who moves first in every other repetition.
+    Alternating,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Payoff {
     // From https://en.wikipedia.org/wiki/Prisoner%27s_dilemma#Generalized_form
     temptation_reward: u32,
@@ -0,0 +1,409 @@
+use crate::rng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+const RANDOM_REGULAR_ATTEMPTS: u32 = 1000;
+// Highest node number plus one accepted in an edge list.
+const MAX_EDGE_LIST_NODES: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighborhood {
+    // The four orthogonal neighbors.
+    VonNeumann,
+    // The eight orthogonal and diagonal neighbors.
+    Moore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary {
+    // Opposite edges of the grid are joined, making it a torus.
+    Periodic,
+    // Cells on the edges simply have fewer neighbors.
+    Fixed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    InvalidLine { line: usize, content: String },
+    NodeTooLarge { line: usize, node: usize },
+    NoRegularGraph { nodes: usize, degree: usize },
+    // The degree isn't below the number of nodes, or their product is odd.
+    InvalidDegree { nodes: usize, degree: usize },
+    // No edges per new node, or not more nodes than edges per node.
+    InvalidEdgesPerNode { nodes: usize, edges_per_node: usize },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::InvalidLine { line, content } => {
+                write!(f, "line {} is not an edge: {:?}", line, content)
+            }
+            GraphError::NodeTooLarge { line, node } => write!(
+                f,
+                "line {} names node {}, but graphs have at most {} nodes",
+                line, node, MAX_EDGE_LIST_NODES
+            ),
+            GraphError::NoRegularGraph { nodes, degree } => write!(
+                f,
+                "no {}-regular graph of {} nodes found after {} attempts",
+                degree, nodes, RANDOM_REGULAR_ATTEMPTS
+            ),
+            GraphError::InvalidDegree { nodes, degree } => {
+                write!(f, "no graph of {} nodes has degree {}", nodes, degree)
+            }
+            GraphError::InvalidEdgesPerNode {
+                nodes,
+                edges_per_node,
+            } => write!(
+                f,
+                "a scale-free graph of {} nodes can't add {} edges per node",
+                nodes, edges_per_node
+            ),
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+// Undirected graph without self-loops or parallel edges. Nodes are numbered
+// from zero. Random graphs draw from the `rng` module, so seed it first for a
+// reproducible graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Graph {
+    neighbors: Vec<BTreeSet<usize>>,
+}
+
+impl Graph {
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            neighbors: vec![BTreeSet::new(); nodes],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    // Adds an edge between two different nodes. Returns whether it is new.
+    pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+        if a == b || self.neighbors[a].contains(&b) {
+            return false;
+        }
+        self.neighbors[a].insert(b);
+        self.neighbors[b].insert(a);
+        true
+    }
+
+    fn remove_edge(&mut self, a: usize, b: usize) {
+        self.neighbors[a].remove(&b);
+        self.neighbors[b].remove(&a);
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.neighbors[a].contains(&b)
+    }
+
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors[node].iter().copied()
+    }
+
+    pub fn degree(&self, node: usize) -> usize {
+        self.neighbors[node].len()
+    }
+
+    // Every edge once, as `(a, b)` with `a < b`, in increasing order.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(a, neighbors)| neighbors.range(a + 1..).map(move |b| (a, *b)))
+            .collect()
+    }
+
+    // Every node linked to its `k` nearest nodes on each side.
+    pub fn ring(nodes: usize, k: usize) -> Self {
+        let mut graph = Self::new(nodes);
+        for node in 0..nodes {
+            for distance in 1..=k {
+                graph.add_edge(node, (node + distance) % nodes);
+            }
+        }
+        graph
+    }
+
+    // Cells of a `width` x `height` grid numbered row by row.
+    pub fn grid(
+        width: usize,
+        height: usize,
+        neighborhood: Neighborhood,
+        boundary: Boundary,
+    ) -> Self {
+        let offsets: &[(isize, isize)] = match neighborhood {
+            Neighborhood::VonNeumann => &[(1, 0), (0, 1), (-1, 0), (0, -1)],
+            Neighborhood::Moore => &[
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+            ],
+        };
+        let mut graph = Self::new(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                for (dx, dy) in offsets {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    let neighbor = match boundary {
+                        Boundary::Periodic => Some((
+                            nx.rem_euclid(width as isize) as usize,
+                            ny.rem_euclid(height as isize) as usize,
+                        )),
+                        Boundary::Fixed => {
+                            if (0..width as isize).contains(&nx)
+                                && (0..height as isize).contains(&ny)
+                            {
+                                Some((nx as usize, ny as usize))
+                            } else {
+                                None
+                            }
+                        }
+                    };
+                    if let Some((nx, ny)) = neighbor {
+                        graph.add_edge(y * width + x, ny * width + nx);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    // Uniformly random graph in which every node has `degree` neighbors,
+    // built by randomly pairing edge stubs until no pairing is invalid.
+    // Gives up after `RANDOM_REGULAR_ATTEMPTS` invalid pairings.
+    pub fn random_regular(nodes: usize, degree: usize) -> Result<Self, GraphError> {
+        if degree >= nodes || !(nodes * degree).is_multiple_of(2) {
+            return Err(GraphError::InvalidDegree { nodes, degree });
+        }
+        let mut stubs: Vec<usize> = (0..nodes)
+            .flat_map(|node| std::iter::repeat_n(node, degree))
+            .collect();
+        for _ in 0..RANDOM_REGULAR_ATTEMPTS {
+            rng::with_rng(|rng| stubs.shuffle(rng));
+            let mut graph = Self::new(nodes);
+            if stubs.chunks(2).all(|pair| graph.add_edge(pair[0], pair[1])) {
+                return Ok(graph);
+            }
+        }
+        Err(GraphError::NoRegularGraph { nodes, degree })
+    }
+
+    // Watts-Strogatz graph: a ring where the far end of every edge is moved
+    // to a random node with probability `rewiring_probability`.
+    pub fn small_world(nodes: usize, k: usize, rewiring_probability: f64) -> Self {
+        let mut graph = Self::ring(nodes, k);
+        for (a, b) in graph.edges() {
+            if graph.degree(a) == nodes - 1 || !rng::random_bool(rewiring_probability) {
+                continue;
+            }
+            let target = loop {
+                let target = rng::with_rng(|rng| rng.gen_range(0..nodes));
+                if target != a && !graph.has_edge(a, target) {
+                    break target;
+                }
+            };
+            graph.remove_edge(a, b);
+            graph.add_edge(a, target);
+        }
+        graph
+    }
+
+    // Barabási-Albert graph: starting from a complete graph of
+    // `edges_per_node + 1` nodes, every new node links to `edges_per_node`
+    // existing nodes chosen with probability proportional to their degree.
+    pub fn scale_free(nodes: usize, edges_per_node: usize) -> Result<Self, GraphError> {
+        if edges_per_node == 0 || nodes <= edges_per_node {
+            return Err(GraphError::InvalidEdgesPerNode {
+                nodes,
+                edges_per_node,
+            });
+        }
+        let mut graph = Self::new(nodes);
+        // Every node appears once per edge end, so a uniform draw from it is
+        // proportional to degree.
+        let mut edge_ends = Vec::new();
+        for a in 0..=edges_per_node {
+            for b in a + 1..=edges_per_node {
+                graph.add_edge(a, b);
+                edge_ends.extend([a, b]);
+            }
+        }
+        for node in edges_per_node + 1..nodes {
+            let mut targets = BTreeSet::new();
+            while targets.len() < edges_per_node {
+                let end = rng::with_rng(|rng| rng.gen_range(0..edge_ends.len()));
+                targets.insert(edge_ends[end]);
+            }
+            for target in targets {
+                graph.add_edge(node, target);
+                edge_ends.extend([node, target]);
+            }
+        }
+        Ok(graph)
+    }
+
+    // Parses one edge per line as two node numbers separated by whitespace.
+    // Empty lines and lines starting with '#' are skipped. The graph has as
+    // many nodes as the highest node number plus one, which can't exceed
+    // `MAX_EDGE_LIST_NODES`.
+    pub fn from_edge_list(text: &str) -> Result<Self, GraphError> {
+        let mut edges = Vec::new();
+        for (line, content) in text.lines().enumerate() {
+            let trimmed = content.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let invalid = || GraphError::InvalidLine {
+                line: line + 1,
+                content: content.to_string(),
+            };
+            let nodes: Vec<usize> = trimmed
+                .split_whitespace()
+                .map(|node| node.parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?;
+            match nodes[..] {
+                [a, b] => edges.push((a, b)),
+                _ => return Err(invalid()),
+            }
+            if let Some(node) = nodes.into_iter().find(|node| *node >= MAX_EDGE_LIST_NODES) {
+                return Err(GraphError::NodeTooLarge {
+                    line: line + 1,
+                    node,
+                });
+            }
+        }
+        let nodes = edges.iter().map(|(a, b)| a.max(b) + 1).max().unwrap_or(0);
+        let mut graph = Self::new(nodes);
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring() {
+        let graph = Graph::ring(6, 2);
+        assert!((0..6).all(|node| graph.degree(node) == 4));
+        assert_eq!(graph.neighbors(0).collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+        assert_eq!(graph.edges().len(), 12);
+    }
+
+    #[test]
+    fn periodic_grids() {
+        let von_neumann = Graph::grid(4, 5, Neighborhood::VonNeumann, Boundary::Periodic);
+        assert_eq!(von_neumann.len(), 20);
+        assert!((0..20).all(|cell| von_neumann.degree(cell) == 4));
+        assert!(von_neumann.has_edge(0, 3));
+        assert!(von_neumann.has_edge(0, 16));
+        let moore = Graph::grid(4, 5, Neighborhood::Moore, Boundary::Periodic);
+        assert!((0..20).all(|cell| moore.degree(cell) == 8));
+        assert!(moore.has_edge(0, 19));
+    }
+
+    #[test]
+    fn fixed_grids() {
+        let von_neumann = Graph::grid(3, 3, Neighborhood::VonNeumann, Boundary::Fixed);
+        assert_eq!(von_neumann.degree(0), 2);
+        assert_eq!(von_neumann.degree(1), 3);
+        assert_eq!(von_neumann.degree(4), 4);
+        let moore = Graph::grid(3, 3, Neighborhood::Moore, Boundary::Fixed);
+        assert_eq!(moore.degree(0), 3);
+        assert_eq!(moore.degree(4), 8);
+    }
+
+    #[test]
+    fn random_regular() {
+        rng::seed(1);
+        let graph = Graph::random_regular(20, 3).unwrap();
+        assert!((0..20).all(|node| graph.degree(node) == 3));
+        assert_eq!(
+            Graph::random_regular(2, 1).map(|graph| graph.edges()),
+            Ok(vec![(0, 1)])
+        );
+        assert_eq!(
+            Graph::random_regular(5, 3),
+            Err(GraphError::InvalidDegree {
+                nodes: 5,
+                degree: 3
+            })
+        );
+        assert_eq!(
+            Graph::random_regular(3, 3),
+            Err(GraphError::InvalidDegree {
+                nodes: 3,
+                degree: 3
+            })
+        );
+    }
+
+    #[test]
+    fn small_world_keeps_edge_count() {
+        rng::seed(2);
+        assert_eq!(Graph::small_world(20, 2, 0.0), Graph::ring(20, 2));
+        let rewired = Graph::small_world(20, 2, 0.5);
+        assert_eq!(rewired.edges().len(), 40);
+        assert_ne!(rewired, Graph::ring(20, 2));
+    }
+
+    #[test]
+    fn scale_free() {
+        rng::seed(3);
+        let graph = Graph::scale_free(50, 2).unwrap();
+        assert_eq!(graph.edges().len(), 3 + 47 * 2);
+        assert!((3..50).all(|node| graph.degree(node) >= 2));
+        assert_eq!(
+            Graph::scale_free(5, 0),
+            Err(GraphError::InvalidEdgesPerNode {
+                nodes: 5,
+                edges_per_node: 0
+            })
+        );
+    }
+
+    #[test]
+    fn edge_list() {
+        let graph = Graph::from_edge_list("# a path\n0 1\n\n1  2\n2 1\n").unwrap();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.edges(), vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            Graph::from_edge_list("0 1\n1 x\n"),
+            Err(GraphError::InvalidLine {
+                line: 2,
+                content: "1 x".to_string()
+            })
+        );
+        assert!(Graph::from_edge_list("0 1 2").is_err());
+        assert_eq!(
+            Graph::from_edge_list("0 1\n0 2000000\n"),
+            Err(GraphError::NodeTooLarge {
+                line: 2,
+                node: 2_000_000
+            })
+        );
+    }
+}
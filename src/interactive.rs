@@ -0,0 +1,222 @@
+use crate::game::{Move, Payoff};
+use crate::player::{self, GamePlay, Player};
+use crate::strategy::tit_for_tat::TitForTat;
+use crate::strategy::Strategy;
+use std::io::{self, BufRead, Write};
+use std::sync::Mutex;
+
+const HUMAN_NAME: &str = "you";
+const OPPONENT_NAME: &str = "opponent";
+
+// Plays whatever move the human typed last.
+struct HumanMove {
+    next_move: Mutex<Move>,
+}
+
+impl Strategy for HumanMove {
+    fn play(&self, _past_games: &[GamePlay]) -> Move {
+        *self.next_move.lock().unwrap()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InteractiveSummary {
+    pub rounds: u32,
+    pub human_score: u64,
+    pub opponent_score: u64,
+    // What TitForTat earns against a fresh copy of the same opponent over the
+    // same number of rounds.
+    pub tit_for_tat_score: u64,
+}
+
+fn parse_move(line: &str) -> Option<Move> {
+    match line.trim().to_ascii_uppercase().as_str() {
+        "C" => Some(Move::Collaborate),
+        "D" => Some(Move::Defect),
+        _ => None,
+    }
+}
+
+// Reads moves until one is valid. Returns None when the input ends or the
+// human quits.
+fn read_move<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    round: u32,
+    rounds: u32,
+) -> io::Result<Option<Move>> {
+    loop {
+        write!(
+            output,
+            "Round {}/{}. Your move [C/D, Q to quit]: ",
+            round, rounds
+        )?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 || line.trim().eq_ignore_ascii_case("q") {
+            return Ok(None);
+        }
+        match parse_move(&line) {
+            Some(next_move) => return Ok(Some(next_move)),
+            None => writeln!(output, "Please type C or D.")?,
+        }
+    }
+}
+
+fn format_moves(history: &[GamePlay], select: fn(&GamePlay) -> Move) -> String {
+    history
+        .iter()
+        .map(|game| select(game).to_string())
+        .collect()
+}
+
+pub fn play_interactive<R: BufRead, W: Write>(
+    opponent: &dyn Strategy,
+    payoff: &Payoff,
+    rounds: u32,
+    mut input: R,
+    output: &mut W,
+) -> io::Result<InteractiveSummary> {
+    let human_strategy = HumanMove {
+        next_move: Mutex::new(Move::Collaborate),
+    };
+    let mut human = Player::new(HUMAN_NAME, &human_strategy);
+    let mut opponent_player = Player::new(OPPONENT_NAME, opponent);
+    writeln!(
+        output,
+        "Playing {} rounds against {}.",
+        rounds,
+        opponent.name()
+    )?;
+    let mut rounds_played = 0;
+    for round in 1..=rounds {
+        let next_move = match read_move(&mut input, output, round, rounds)? {
+            Some(next_move) => next_move,
+            None => break,
+        };
+        *human_strategy.next_move.lock().unwrap() = next_move;
+        let (human_before, opponent_before) = (human.score(), opponent_player.score());
+        player::play_games(&mut human, &mut opponent_player, payoff, 1);
+        rounds_played += 1;
+        let history = human.history(OPPONENT_NAME);
+        let last_game = history.last().expect("a game was just played");
+        writeln!(
+            output,
+            "You played {}, {} played {}. Payoffs: {} - {}. Score: {} - {}.",
+            last_game.my_move,
+            opponent.name(),
+            last_game.their_move,
+            human.score() - human_before,
+            opponent_player.score() - opponent_before,
+            human.score(),
+            opponent_player.score()
+        )?;
+        writeln!(
+            output,
+            "  You:      {}",
+            format_moves(history, |g| g.my_move)
+        )?;
+        writeln!(
+            output,
+            "  Opponent: {}",
+            format_moves(history, |g| g.their_move)
+        )?;
+    }
+
+    let tit_for_tat = TitForTat {};
+    let mut tit_for_tat_player = Player::new(HUMAN_NAME, &tit_for_tat);
+    let mut fresh_opponent = Player::new(OPPONENT_NAME, opponent);
+    player::play_games(
+        &mut tit_for_tat_player,
+        &mut fresh_opponent,
+        payoff,
+        rounds_played,
+    );
+    let summary = InteractiveSummary {
+        rounds: rounds_played,
+        human_score: human.score(),
+        opponent_score: opponent_player.score(),
+        tit_for_tat_score: tit_for_tat_player.score(),
+    };
+    writeln!(
+        output,
+        "Final score after {} rounds: you {} - {} {}. TitForTat would have scored {}.",
+        summary.rounds,
+        summary.human_score,
+        opponent.name(),
+        summary.opponent_score,
+        summary.tit_for_tat_score
+    )?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+
+    const TEST_PAYOFF: Payoff = Payoff::new(5, 3, 1, 0);
+
+    #[test]
+    fn full_game_against_nasty() {
+        let mut output = Vec::new();
+        let summary = play_interactive(
+            &Nasty {},
+            &TEST_PAYOFF,
+            3,
+            "c\nd\nx\nD\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            InteractiveSummary {
+                rounds: 3,
+                human_score: 2,
+                opponent_score: 7,
+                tit_for_tat_score: 2,
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Please type C or D."));
+        assert!(output.contains("  You:      CDD\n  Opponent: DDD\n"));
+        assert!(output.contains("Payoffs: 0 - 5. Score: 0 - 5."));
+    }
+
+    #[test]
+    fn quitting_early_against_naive() {
+        let mut output = Vec::new();
+        let summary = play_interactive(
+            &Naive {},
+            &TEST_PAYOFF,
+            10,
+            "D\nq\nC\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            InteractiveSummary {
+                rounds: 1,
+                human_score: 5,
+                opponent_score: 0,
+                tit_for_tat_score: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn end_of_input() {
+        let mut output = Vec::new();
+        let summary =
+            play_interactive(&Naive {}, &TEST_PAYOFF, 10, "".as_bytes(), &mut output).unwrap();
+        assert_eq!(summary.rounds, 0);
+        assert_eq!(summary.human_score, 0);
+        assert_eq!(summary.tit_for_tat_score, 0);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod combinatorics;
+pub mod cycle;
+pub mod equivalence;
+pub mod evolution;
+pub mod fingerprint;
+pub mod game;
+pub mod graph;
+pub mod interactive;
+pub mod player;
+pub mod population;
+pub mod progress;
+pub mod rng;
+pub mod statistics;
+pub mod strategy;
+pub mod tournament;
+pub mod transcript;
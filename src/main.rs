@@ -1,31 +1,641 @@
-mod combinatorics;
-mod game;
-mod player;
-mod population;
-mod strategy;
-mod tournament;
-
-use population::simulate_populations;
-use std::env;
-use strategy::all_strategies;
-use tournament::Tournament;
+use prisoners_dilemma::evolution::{
+    evolve_lattice, fixation, replicator_dynamics, wright_fisher, Lattice, LatticeSettings,
+    MoranSettings, MutationKernel, PayoffMatrix, ReplicatorSettings, UpdateMode, UpdateRule,
+    WrightFisherSettings,
+};
+use prisoners_dilemma::game::{Payoff, Timing};
+use prisoners_dilemma::graph::{Boundary, Graph, Neighborhood};
+use prisoners_dilemma::player::Player;
+use prisoners_dilemma::population::{simulate_populations_with, Checkpointing, SimulationOptions};
+use prisoners_dilemma::progress::{CancellationToken, Monitor, Progress};
+use prisoners_dilemma::strategy::{all_strategies, find_strategy, Strategy};
+use prisoners_dilemma::tournament::{
+    seeding, ComplexityCost, DrawTiebreak, Elimination, FaultPolicy, ProbePanel, Tournament,
+    TournamentBuilder,
+};
+use prisoners_dilemma::transcript::{render_grid, Transcript, Verification};
+use prisoners_dilemma::{equivalence, fingerprint, interactive};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+const FINGERPRINT_RESOLUTION: usize = 10;
+const FINGERPRINT_TURNS: u32 = 50;
+const FINGERPRINT_REPETITIONS: u32 = 20;
+const INTERACTIVE_ROUNDS: u32 = 10;
+const SWISS_ROUNDS: u32 = 3;
+const TOURNAMENT_REPETITIONS: u32 = 5;
+const EQUIVALENCE_DEPTH: usize = 10;
+const EQUIVALENCE_PROBE_TURNS: usize = 200;
+const SPATIAL_GRID_SIDE: usize = 6;
+const SPATIAL_DEGREE: usize = 4;
+const CHECKPOINT_INTERVAL: usize = 10;
+const MORAN_POPULATION: u32 = 10;
+const MORAN_RUNS: u32 = 1000;
+const WRIGHT_FISHER_INSTANCES: u32 = 10;
+const WRIGHT_FISHER_GENERATIONS: u32 = 10_000;
+const WRIGHT_FISHER_MUTATION: f64 = 0.01;
+const LATTICE_SIDE: usize = 20;
+const LATTICE_STEPS: u32 = 50;
+const FERMI_TEMPERATURE: f64 = 0.5;
+
+// Shows the progress reported to the monitor on one line of stderr, and
+// cancels the run on Ctrl-C. The thread ends once the monitor is dropped.
+fn progress_line() -> (Monitor, JoinHandle<()>) {
+    let cancellation = CancellationToken::default();
+    let interrupted = cancellation.clone();
+    ctrlc::set_handler(move || interrupted.cancel()).expect("Ctrl-C handler");
+    let (sender, receiver) = mpsc::channel::<Progress>();
+    let printer = thread::spawn(move || {
+        let mut shown = false;
+        for progress in receiver {
+            eprint!("\r{}    ", progress);
+            shown = true;
+        }
+        if shown {
+            eprintln!();
+        }
+    });
+    let monitor = Monitor {
+        progress: Some(sender),
+        cancellation: Some(cancellation),
+    };
+    (monitor, printer)
+}
 
 // TODO: Figure out how to do flags in Rust in a better manner.
 fn create_tournament(args: &[String]) -> Tournament {
-    if args.len() > 1 && args[1] == "axelrod" {
-        Tournament::axelrod_tournament()
+    let builder = Tournament::builder().self_play(args.len() > 1 && args[1] == "axelrod");
+    with_complexity_cost(builder, args)
+        .build()
+        .unwrap_or_else(|error| {
+            eprintln!("Invalid tournament: {}", error);
+            process::exit(1)
+        })
+}
+
+// Charges every remembered turn the cost given after --memory-cost and every
+// state the one after --state-cost, on every turn, and every match the one
+// after --match-cost.
+fn with_complexity_cost(builder: TournamentBuilder, args: &[String]) -> TournamentBuilder {
+    let cost = |flag: &str| -> f64 {
+        flag_value(args, flag).map_or(0.0, |cost| {
+            cost.parse().unwrap_or_else(|_| {
+                eprintln!("Invalid cost after {}: {}", flag, cost);
+                process::exit(1)
+            })
+        })
+    };
+    builder.complexity_cost(ComplexityCost {
+        per_memory_turn: cost("--memory-cost"),
+        per_state: cost("--state-cost"),
+        per_match: cost("--match-cost"),
+    })
+}
+
+fn strategies_from_names(names: &[String]) -> Vec<Box<dyn Strategy>> {
+    names
+        .iter()
+        .map(|name| {
+            find_strategy(name).unwrap_or_else(|| {
+                eprintln!("Unknown strategy: {}", name);
+                process::exit(1)
+            })
+        })
+        .collect()
+}
+
+fn print_fingerprints(strategy_names: &[String]) {
+    let strategies = if strategy_names.is_empty() {
+        all_strategies()
     } else {
-        Tournament::all_pairs()
+        strategies_from_names(strategy_names)
+    };
+    let fingerprints: Vec<_> = strategies
+        .iter()
+        .map(|strategy| {
+            fingerprint::fingerprint(
+                strategy.as_ref(),
+                &Payoff::default(),
+                FINGERPRINT_RESOLUTION,
+                FINGERPRINT_TURNS,
+                FINGERPRINT_REPETITIONS,
+            )
+        })
+        .collect();
+    for fingerprint in &fingerprints {
+        println!("{}", fingerprint);
+    }
+    for (i, first) in fingerprints.iter().enumerate() {
+        for second in &fingerprints[i + 1..] {
+            println!(
+                "{} ~ {}: {:.3}",
+                first.strategy,
+                second.strategy,
+                first.distance(second)
+            );
+        }
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let tournament = create_tournament(&args);
+fn print_equivalence(strategy_names: &[String]) {
+    if strategy_names.len() != 2 {
+        eprintln!("Usage: equivalence <strategy> <strategy>");
+        process::exit(1);
+    }
+    let strategies = strategies_from_names(strategy_names);
+    let equivalence = equivalence::compare(
+        strategies[0].as_ref(),
+        strategies[1].as_ref(),
+        EQUIVALENCE_DEPTH,
+        &all_strategies(),
+        EQUIVALENCE_PROBE_TURNS,
+    );
+    println!(
+        "{} vs {}: {}",
+        strategies[0].name(),
+        strategies[1].name(),
+        equivalence
+    );
+}
+
+fn play_interactive(args: &[String]) {
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("Usage: play <strategy> [rounds]");
+        process::exit(1);
+    }
+    let opponent = strategies_from_names(&args[..1]).remove(0);
+    let rounds = args.get(1).map_or(INTERACTIVE_ROUNDS, |rounds| {
+        rounds.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number of rounds: {}", rounds);
+            process::exit(1)
+        })
+    });
+    interactive::play_interactive(
+        opponent.as_ref(),
+        &Payoff::default(),
+        rounds,
+        io::stdin().lock(),
+        &mut io::stdout(),
+    )
+    .expect("terminal I/O");
+}
+
+// Value following a flag, such as FILE in `--transcript FILE`.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    Some(args.get(position + 1).unwrap_or_else(|| {
+        eprintln!("Missing value after {}", flag);
+        process::exit(1)
+    }))
+}
+
+fn print_tournament(args: &[String]) {
+    let transcript_path = flag_value(args, "--transcript");
+    let mut builder = Tournament::builder();
+    if let Some(budget) = flag_value(args, "--move-budget") {
+        let milliseconds = budget.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid move budget: {}", budget);
+            process::exit(1)
+        });
+        builder = builder.move_budget(Duration::from_millis(milliseconds));
+    }
+    if args.iter().any(|arg| arg == "--disqualify") {
+        builder = builder.fault_policy(FaultPolicy::Disqualify);
+    }
+    let tournament = with_complexity_cost(builder, args)
+        .self_play(args.iter().any(|arg| arg == "axelrod"))
+        .repetitions(TOURNAMENT_REPETITIONS)
+        .record_moves(transcript_path.is_some())
+        .timing(if args.iter().any(|arg| arg == "--alternating") {
+            Timing::Alternating
+        } else {
+            Timing::Simultaneous
+        })
+        .build()
+        .unwrap_or_else(|error| {
+            eprintln!("Invalid tournament: {}", error);
+            process::exit(1)
+        });
+    let strategies = all_strategies();
+    let mut players: Vec<_> = strategies
+        .iter()
+        .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+        .collect();
+    let (monitor, printer) = progress_line();
+    let result = tournament.play_games_monitored(&mut players, &monitor);
+    drop(monitor);
+    printer.join().expect("progress line");
+    if result.cancelled {
+        eprintln!("Interrupted, showing the matches played so far");
+    }
+    if let Some(path) = transcript_path {
+        Transcript::from_result(&tournament, &result)
+            .save(Path::new(path))
+            .unwrap_or_else(|error| {
+                eprintln!("Cannot write {}: {}", path, error);
+                process::exit(1)
+            });
+    }
+    if args.iter().any(|arg| arg == "--json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).expect("serializable result")
+        );
+        return;
+    }
+    println!("{}", result.ranking());
+    for incident in result.incidents() {
+        println!(
+            "{} against {} on turn {}: {}",
+            result.players[incident.player],
+            result.players[incident.opponent],
+            incident.turn + 1,
+            incident.fault
+        );
+    }
+}
+
+fn print_swiss(args: &[String]) {
+    let rounds = args.first().map_or(SWISS_ROUNDS, |rounds| {
+        rounds.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number of rounds: {}", rounds);
+            process::exit(1)
+        })
+    });
+    let strategies = all_strategies();
+    let players: Vec<_> = strategies
+        .iter()
+        .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+        .collect();
+    let result = Tournament::all_pairs().play_swiss(&players, rounds);
+    for (rank, standing) in result.standings.iter().enumerate() {
+        println!(
+            "{}. {}: {} points, Buchholz {}, payoff {}",
+            rank + 1,
+            standing.name,
+            standing.points,
+            standing.buchholz,
+            standing.total_payoff
+        );
+    }
+}
+
+fn print_knockout(args: &[String]) {
+    let elimination = if args.iter().any(|arg| arg == "double") {
+        Elimination::Double
+    } else {
+        Elimination::Single
+    };
+    let tournament = Tournament::all_pairs();
+    let strategies = all_strategies();
+    let mut players: Vec<_> = strategies
+        .iter()
+        .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+        .collect();
+    let round_robin = tournament.play_games(&mut players);
+    let bracket = tournament.play_knockout(
+        &players,
+        &seeding(&round_robin),
+        elimination,
+        DrawTiebreak::Replay { max_replays: 2 },
+    );
+    println!("{}", bracket);
+}
+
+fn create_graph(args: &[String]) -> Graph {
+    let nodes = SPATIAL_GRID_SIDE * SPATIAL_GRID_SIDE;
+    match args.first().map(String::as_str) {
+        None | Some("grid") => Graph::grid(
+            SPATIAL_GRID_SIDE,
+            SPATIAL_GRID_SIDE,
+            Neighborhood::VonNeumann,
+            Boundary::Periodic,
+        ),
+        Some("moore") => Graph::grid(
+            SPATIAL_GRID_SIDE,
+            SPATIAL_GRID_SIDE,
+            Neighborhood::Moore,
+            Boundary::Periodic,
+        ),
+        Some("ring") => Graph::ring(nodes, SPATIAL_DEGREE / 2),
+        Some("regular") => Graph::random_regular(nodes, SPATIAL_DEGREE).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1)
+        }),
+        Some("small-world") => Graph::small_world(nodes, SPATIAL_DEGREE / 2, 0.1),
+        Some("scale-free") => {
+            Graph::scale_free(nodes, SPATIAL_DEGREE / 2).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                process::exit(1)
+            })
+        }
+        Some(path) => {
+            let text = fs::read_to_string(path).unwrap_or_else(|error| {
+                eprintln!("Cannot read {}: {}", path, error);
+                process::exit(1)
+            });
+            Graph::from_edge_list(&text).unwrap_or_else(|error| {
+                eprintln!("Invalid edge list {}: {}", path, error);
+                process::exit(1)
+            })
+        }
+    }
+}
+
+// Spreads all strategies over the nodes of a graph and compares their average
+// per-turn scores against neighbors with those in a round robin of the same
+// players.
+fn print_spatial(args: &[String]) {
+    let graph = create_graph(args);
+    let strategies = all_strategies();
+    let new_players = || -> Vec<Player> {
+        (0..graph.len())
+            .map(|node| {
+                let strategy = strategies[node % strategies.len()].as_ref();
+                Player::new(&format!("{}_{}", strategy.name(), node), strategy)
+            })
+            .collect()
+    };
+    let tournament = Tournament::all_pairs();
+    let spatial = tournament.play_on_graph(&mut new_players(), &graph);
+    let well_mixed = tournament.play_games(&mut new_players());
+    let (spatial_scores, mixed_scores) =
+        (spatial.per_turn_averages(), well_mixed.per_turn_averages());
+    println!("strategy: on graph / well mixed (per turn)");
+    for strategy in &strategies {
+        let nodes: Vec<_> = (0..graph.len())
+            .filter(|node| spatial.strategies[*node] == strategy.name())
+            .collect();
+        if nodes.is_empty() {
+            continue;
+        }
+        let average = |scores: &[f64]| {
+            nodes.iter().map(|node| scores[*node]).sum::<f64>() / nodes.len() as f64
+        };
+        println!(
+            "{}: {:.2} / {:.2}",
+            strategy.name(),
+            average(&spatial_scores),
+            average(&mixed_scores)
+        );
+    }
+}
+
+// Shows every match of a transcript and checks that the deterministic ones
+// still play out the same. Exits with an error if one doesn't.
+fn replay(args: &[String]) {
+    let Some(path) = args.first() else {
+        eprintln!("Usage: replay <transcript>");
+        process::exit(1)
+    };
+    let transcript = Transcript::load(Path::new(path)).unwrap_or_else(|error| {
+        eprintln!("Cannot read {}: {}", path, error);
+        process::exit(1)
+    });
+    // Colors only go to a terminal, not to a file or pipe.
+    let colored = io::stdout().is_terminal();
+    let mut diverged = false;
+    for recorded in &transcript.matches {
+        let verification = transcript.verify(recorded);
+        diverged |= matches!(verification, Verification::Diverged { .. });
+        println!("Repetition {}: {}", recorded.repetition, verification);
+        println!("{}\n", render_grid(recorded, colored));
+    }
+    if diverged {
+        process::exit(1);
+    }
+}
+
+fn print_probe(args: &[String]) {
+    if args.len() != 1 {
+        eprintln!("Usage: probe <strategy>");
+        process::exit(1);
+    }
+    let candidate = strategies_from_names(args).remove(0);
+    println!("{}", ProbePanel::standard().probe(candidate.as_ref()));
+}
+
+fn print_moran(args: &[String]) {
+    if !(2..=3).contains(&args.len()) {
+        eprintln!("Usage: moran <mutant> <resident> [population]");
+        process::exit(1);
+    }
+    let population = args.get(2).map_or(MORAN_POPULATION, |population| {
+        population
+            .parse()
+            .ok()
+            .filter(|population| *population >= 2)
+            .unwrap_or_else(|| {
+                eprintln!("Invalid population: {}", population);
+                process::exit(1)
+            })
+    });
+    let strategies = strategies_from_names(&args[..2]);
+    let matrix = PayoffMatrix::new(&strategies, &Tournament::all_pairs());
+    let estimate = fixation(
+        &matrix,
+        0,
+        1,
+        population,
+        &MoranSettings::default(),
+        MORAN_RUNS,
+    );
+    println!(
+        "Fixation probability of {} among {}: {:.3} (neutral: {:.3})",
+        matrix.strategies[0],
+        matrix.strategies[1],
+        estimate.probability,
+        1.0 / f64::from(population)
+    );
+    if let Some(mean_time) = estimate.mean_time {
+        println!("Mean fixation time: {:.1} steps", mean_time);
+    }
+}
+
+fn print_ecological(args: &[String]) {
+    let mut settings = ReplicatorSettings::default();
+    if let Some(generations) = args.first() {
+        settings.generations = generations.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number of generations: {}", generations);
+            process::exit(1)
+        });
+    }
+    let tournament = Tournament::builder()
+        .repetitions(TOURNAMENT_REPETITIONS)
+        .build()
+        .unwrap_or_else(|error| {
+            eprintln!("Invalid tournament: {}", error);
+            process::exit(1)
+        });
     let strategies = all_strategies();
-    let strategy_wins =
-        simulate_populations(&strategies, /* max_player_instances= */ 3, &tournament);
-    for (strategy, wins_num) in &strategy_wins {
+    let matrix = PayoffMatrix::new(&strategies, &tournament);
+    let outcome = replicator_dynamics(&matrix, &vec![1.0; matrix.len()], &settings);
+    let mut shares: Vec<_> = matrix
+        .strategies
+        .iter()
+        .zip(outcome.final_shares())
+        .collect();
+    shares.sort_by(|a, b| b.1.total_cmp(a.1));
+    for (strategy, share) in shares {
+        println!("{}: {:.4}", strategy, share);
+    }
+    match outcome.converged_at {
+        Some(generation) => println!("Converged after {} generations", generation),
+        None => println!(
+            "Not converged after {} generations",
+            outcome.trajectory.len() - 1
+        ),
+    }
+}
+
+fn print_wright_fisher(args: &[String]) {
+    let seed = args.first().map_or(0, |seed| {
+        seed.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid seed: {}", seed);
+            process::exit(1)
+        })
+    });
+    let tournament = Tournament::builder()
+        .seed(seed)
+        .build()
+        .unwrap_or_else(|error| {
+            eprintln!("Invalid tournament: {}", error);
+            process::exit(1)
+        });
+    let strategies = all_strategies();
+    let matrix = PayoffMatrix::new(&strategies, &tournament);
+    let settings = WrightFisherSettings {
+        intensity: 1.0,
+        mutation: MutationKernel::uniform(matrix.len(), WRIGHT_FISHER_MUTATION),
+        generations: WRIGHT_FISHER_GENERATIONS,
+        burn_in: WRIGHT_FISHER_GENERATIONS / 10,
+        seed,
+    };
+    let initial = vec![WRIGHT_FISHER_INSTANCES; matrix.len()];
+    let outcome = wright_fisher(&matrix, &initial, &settings);
+    let mut abundance: Vec<_> = matrix
+        .strategies
+        .iter()
+        .zip(&outcome.mean_abundance)
+        .collect();
+    abundance.sort_by(|a, b| b.1.total_cmp(a.1));
+    for (strategy, mean) in abundance {
+        println!("{}: {:.4}", strategy, mean);
+    }
+}
+
+fn print_lattice(args: &[String]) {
+    let rule = match args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map(String::as_str)
+    {
+        None | Some("imitate") => UpdateRule::ImitateBest,
+        Some("proportional") => UpdateRule::Proportional,
+        Some("fermi") => UpdateRule::Fermi {
+            temperature: FERMI_TEMPERATURE,
+        },
+        Some(other) => {
+            eprintln!("Unknown update rule: {}", other);
+            process::exit(1)
+        }
+    };
+    let mode = if args.iter().any(|arg| arg == "--async") {
+        UpdateMode::Asynchronous
+    } else {
+        UpdateMode::Synchronous
+    };
+    let boundary = if args.iter().any(|arg| arg == "--fixed") {
+        Boundary::Fixed
+    } else {
+        Boundary::Periodic
+    };
+    let strategies = all_strategies();
+    let matrix = PayoffMatrix::new(&strategies, &Tournament::all_pairs());
+    let lattice = Lattice::random(
+        LATTICE_SIDE,
+        LATTICE_SIDE,
+        matrix.len(),
+        Neighborhood::Moore,
+        boundary,
+    );
+    let settings = LatticeSettings {
+        rule,
+        mode,
+        steps: LATTICE_STEPS,
+        snapshot_interval: LATTICE_STEPS,
+        seed: None,
+    };
+    let outcome = evolve_lattice(&matrix, lattice, &settings);
+    let (initial, last) = (&outcome.counts[0], outcome.counts.last().expect("counts"));
+    for (index, strategy) in matrix.strategies.iter().enumerate() {
+        println!(
+            "{} {}: {} -> {}",
+            index, strategy, initial[index], last[index]
+        );
+    }
+    if let Some(snapshot) = outcome.snapshots.last() {
+        println!("\nStep {}:\n{}", snapshot.step, snapshot);
+    }
+}
+
+fn simulate(args: &[String]) {
+    let tournament = create_tournament(args);
+    let (monitor, printer) = progress_line();
+    let checkpoint = flag_value(args, "--checkpoint");
+    let options = SimulationOptions {
+        cache_matches: args.iter().any(|arg| arg == "--cache"),
+        checkpointing: checkpoint.map(|path| Checkpointing {
+            path: PathBuf::from(path),
+            interval: CHECKPOINT_INTERVAL,
+            resume: args.iter().any(|arg| arg == "--resume"),
+        }),
+        monitor,
+    };
+    let strategies = all_strategies();
+    let outcome = simulate_populations_with(
+        &strategies,
+        /* max_player_instances= */ 3,
+        &tournament,
+        &options,
+    );
+    drop(options);
+    printer.join().expect("progress line");
+    let outcome = outcome.unwrap_or_else(|error| {
+        eprintln!("Simulation failed: {}", error);
+        process::exit(1)
+    });
+    if !outcome.is_complete() {
+        eprintln!(
+            "Interrupted after {} of {} compositions",
+            outcome.completed, outcome.compositions
+        );
+    }
+    for (strategy, wins_num) in &outcome.strategy_wins {
         println!("{}: {}", strategy, wins_num);
     }
 }
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("fingerprint") => print_fingerprints(&args[2..]),
+        Some("equivalence") => print_equivalence(&args[2..]),
+        Some("play") => play_interactive(&args[2..]),
+        Some("tournament") => print_tournament(&args[2..]),
+        Some("swiss") => print_swiss(&args[2..]),
+        Some("knockout") => print_knockout(&args[2..]),
+        Some("spatial") => print_spatial(&args[2..]),
+        Some("replay") => replay(&args[2..]),
+        Some("probe") => print_probe(&args[2..]),
+        Some("moran") => print_moran(&args[2..]),
+        Some("ecological") => print_ecological(&args[2..]),
+        Some("wright-fisher") => print_wright_fisher(&args[2..]),
+        Some("lattice") => print_lattice(&args[2..]),
+        _ => simulate(&args),
+    }
+}
@@ -1,17 +1,40 @@
 use crate::game::{Move, Payoff};
+use crate::rng;
 use crate::strategy::Strategy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct GamePlay {
     pub my_move: Move,
     pub their_move: Move,
 }
 
+// A move of an alternating game, as seen by one of the players.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OrderedMove {
+    pub mine: bool,
+    pub played: Move,
+}
+
+// One turn of a match, as both players chose it and as noise made it.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Round {
+    pub intended: (Move, Move),
+    pub actual: (Move, Move),
+    pub payoffs: (u32, u32),
+}
+
 pub struct Player<'a> {
     pub name: String,
-    score: u32,
+    score: u64,
     past_games: HashMap<String, Vec<GamePlay>>,
+    // Moves in the order played, only kept for alternating games.
+    past_moves: HashMap<String, Vec<OrderedMove>>,
+    // The same moves paired up as every move of the player with the
+    // opponent's reply to it. A second mover's history thus ends with the
+    // move it is about to answer, and leaves out the opening move.
+    replies: HashMap<String, Vec<GamePlay>>,
     strategy: &'a dyn Strategy,
 }
 
@@ -21,30 +44,72 @@ impl<'a> Player<'a> {
             name: name.to_string(),
             score: 0,
             past_games: HashMap::new(),
+            past_moves: HashMap::new(),
+            replies: HashMap::new(),
             strategy,
         }
     }
 
+    // Same name and strategy, without any game played.
+    pub fn fresh(&self) -> Self {
+        Self::new(&self.name, self.strategy)
+    }
+
     pub fn twin(&self) -> Self {
         Self::new(&format!("{}_twin", self.name), self.strategy)
     }
 
     fn play(&mut self, with_player: &str) -> Move {
-        let past_games = self
-            .past_games
-            .entry(with_player.to_string())
-            .or_insert_with(Vec::new);
+        let past_games = self.past_games.entry(with_player.to_string()).or_default();
         self.strategy.play(past_games)
     }
 
+    fn play_alternating(&mut self, with_player: &str) -> Move {
+        let replies = self.replies.entry(with_player.to_string()).or_default();
+        let past_moves = self.past_moves.entry(with_player.to_string()).or_default();
+        self.strategy.play_alternating(replies, past_moves)
+    }
+
+    fn moved(&mut self, with_player: &str, mine: bool, played: Move) {
+        let past_moves = self.past_moves.entry(with_player.to_string()).or_default();
+        if let Some(answered) = past_moves.last().filter(|last| last.mine && !mine) {
+            self.replies
+                .entry(with_player.to_string())
+                .or_default()
+                .push(GamePlay {
+                    my_move: answered.played,
+                    their_move: played,
+                });
+        }
+        past_moves.push(OrderedMove { mine, played });
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn score(&self) -> u32 {
+    pub fn score(&self) -> u64 {
         self.score
     }
 
+    pub fn turns_played(&self) -> usize {
+        self.past_games.values().map(Vec::len).sum()
+    }
+
+    pub fn add_score(&mut self, points: u64) {
+        self.score += points;
+    }
+
+    pub fn history(&self, with_player: &str) -> &[GamePlay] {
+        self.past_games
+            .get(with_player)
+            .map_or(&[], |past_games| past_games.as_slice())
+    }
+
+    pub fn strategy(&self) -> &'a dyn Strategy {
+        self.strategy
+    }
+
     pub fn strategy_name(&self) -> String {
         self.strategy.name()
     }
@@ -52,50 +117,196 @@ impl<'a> Player<'a> {
     fn game_played(&mut self, with_player: &str, my_move: Move, their_move: Move, points: u32) {
         self.past_games
             .entry(with_player.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(GamePlay {
-                my_move: my_move,
-                their_move: their_move,
+                my_move,
+                their_move,
             });
-        self.score += points;
+        self.score += u64::from(points);
+    }
+}
+
+pub struct MatchOutcome {
+    pub scores: (u64, u64),
+    // The games as seen by the first player.
+    pub history: Vec<GamePlay>,
+    pub rounds: Vec<Round>,
+}
+
+impl MatchOutcome {
+    // The same match as seen by the second player.
+    fn swapped(self) -> Self {
+        fn swap<T>((first, second): (T, T)) -> (T, T) {
+            (second, first)
+        }
+        Self {
+            scores: swap(self.scores),
+            history: self
+                .history
+                .into_iter()
+                .map(|game| GamePlay {
+                    my_move: game.their_move,
+                    their_move: game.my_move,
+                })
+                .collect(),
+            rounds: self
+                .rounds
+                .into_iter()
+                .map(|round| Round {
+                    intended: swap(round.intended),
+                    actual: swap(round.actual),
+                    payoffs: swap(round.payoffs),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Plays a match between fresh copies of both players, so neither the players
+// nor their histories with other opponents are touched.
+pub fn play_match(
+    p1: &Player,
+    p2: &Player,
+    payoff: &Payoff,
+    games_num: u32,
+    noise: f64,
+) -> MatchOutcome {
+    let mut first = p1.fresh();
+    let mut second = p2.fresh();
+    let rounds = (0..games_num)
+        .map(|_| play_game(&mut first, &mut second, payoff, noise))
+        .collect();
+    MatchOutcome {
+        scores: (first.score, second.score),
+        history: first.past_games.remove(&second.name).unwrap_or_default(),
+        rounds,
+    }
+}
+
+// Like `play_match`, with the players moving in turns: the first player
+// moves, then the second player answers knowing that move.
+pub fn play_alternating_match(
+    p1: &Player,
+    p2: &Player,
+    payoff: &Payoff,
+    games_num: u32,
+    noise: f64,
+) -> MatchOutcome {
+    let mut first = p1.fresh();
+    let mut second = p2.fresh();
+    let rounds = (0..games_num)
+        .map(|_| play_alternating_game(&mut first, &mut second, payoff, noise))
+        .collect();
+    MatchOutcome {
+        scores: (first.score, second.score),
+        history: first.past_games.remove(&second.name).unwrap_or_default(),
+        rounds,
+    }
+}
+
+// Alternating match of a repeated tournament. The players take turns moving
+// first, from one repetition to the next, so that neither keeps the edge of
+// moving first or second. The outcome is still seen by the first player.
+pub fn play_alternating_repetition(
+    p1: &Player,
+    p2: &Player,
+    payoff: &Payoff,
+    games_num: u32,
+    noise: f64,
+    repetition: u32,
+) -> MatchOutcome {
+    if repetition.is_multiple_of(2) {
+        play_alternating_match(p1, p2, payoff, games_num, noise)
+    } else {
+        play_alternating_match(p2, p1, payoff, games_num, noise).swapped()
     }
 }
 
 pub fn play_games(p1: &mut Player, p2: &mut Player, payoff: &Payoff, games_num: u32) {
+    play_noisy_games(p1, p2, payoff, games_num, 0.0);
+}
+
+// Like `play_games`, but every move is flipped with probability `noise` after
+// the player chose it.
+pub fn play_noisy_games(
+    p1: &mut Player,
+    p2: &mut Player,
+    payoff: &Payoff,
+    games_num: u32,
+    noise: f64,
+) {
     for _ in 0..games_num {
-        play_game(p1, p2, payoff);
+        play_game(p1, p2, payoff, noise);
     }
 }
 
-fn play_game(p1: &mut Player, p2: &mut Player, payoff: &Payoff) {
-    let p1_move = p1.play(&p2.name());
-    let p2_move = p2.play(&p1.name());
+fn apply_noise(intended_move: Move, noise: f64) -> Move {
+    if noise > 0.0 && rng::random_bool(noise) {
+        intended_move.oposite_move()
+    } else {
+        intended_move
+    }
+}
+
+fn play_game(p1: &mut Player, p2: &mut Player, payoff: &Payoff, noise: f64) -> Round {
+    let p1_intended = p1.play(p2.name());
+    let p1_move = apply_noise(p1_intended, noise);
+    let p2_intended = p2.play(p1.name());
+    let p2_move = apply_noise(p2_intended, noise);
     let score = payoff.compute_payoff(p1_move, p2_move);
-    p1.game_played(&p2.name(), p1_move, p2_move, score.0);
-    p2.game_played(&p1.name(), p2_move, p1_move, score.1);
+    p1.game_played(p2.name(), p1_move, p2_move, score.0);
+    p2.game_played(p1.name(), p2_move, p1_move, score.1);
+    Round {
+        intended: (p1_intended, p2_intended),
+        actual: (p1_move, p2_move),
+        payoffs: score,
+    }
+}
+
+fn play_alternating_game(p1: &mut Player, p2: &mut Player, payoff: &Payoff, noise: f64) -> Round {
+    let (p1_name, p2_name) = (p1.name.clone(), p2.name.clone());
+    let p1_intended = p1.play_alternating(&p2_name);
+    let p1_move = apply_noise(p1_intended, noise);
+    p1.moved(&p2_name, true, p1_move);
+    p2.moved(&p1_name, false, p1_move);
+    let p2_intended = p2.play_alternating(&p1_name);
+    let p2_move = apply_noise(p2_intended, noise);
+    p2.moved(&p1_name, true, p2_move);
+    p1.moved(&p2_name, false, p2_move);
+    let score = payoff.compute_payoff(p1_move, p2_move);
+    p1.game_played(&p2_name, p1_move, p2_move, score.0);
+    p2.game_played(&p1_name, p2_move, p1_move, score.1);
+    Round {
+        intended: (p1_intended, p2_intended),
+        actual: (p1_move, p2_move),
+        payoffs: score,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::cell::Cell;
+    use crate::strategy::drunk::Drunk;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use std::sync::Mutex;
 
     const TEST_PAYOFF: Payoff = Payoff::new(5, 3, 1, 0);
 
     struct MockStrategy {
-        next_move: Cell<Move>,
+        next_move: Mutex<Move>,
     }
 
     impl Strategy for MockStrategy {
         fn play(&self, _past_games: &[GamePlay]) -> Move {
-            self.next_move.get()
+            *self.next_move.lock().unwrap()
         }
     }
 
     #[test]
     fn player_and_strategy_name() {
         let mock_strategy = MockStrategy {
-            next_move: Cell::new(Move::Defect),
+            next_move: Mutex::new(Move::Defect),
         };
         let player = Player::new("test_player", &mock_strategy);
         assert_eq!(player.name(), "test_player");
@@ -105,7 +316,7 @@ mod tests {
     #[test]
     fn twin() {
         let mock_strategy = MockStrategy {
-            next_move: Cell::new(Move::Defect),
+            next_move: Mutex::new(Move::Defect),
         };
         let player = Player::new("test_player", &mock_strategy);
         let twin = player.twin();
@@ -117,16 +328,21 @@ mod tests {
     #[test]
     fn one_game_played() {
         let defect_strategy = MockStrategy {
-            next_move: Cell::new(Move::Defect),
+            next_move: Mutex::new(Move::Defect),
         };
         let mut defect_player = Player::new("defect_player", &defect_strategy);
         let collaborate_strategy = MockStrategy {
-            next_move: Cell::new(Move::Collaborate),
+            next_move: Mutex::new(Move::Collaborate),
         };
         let mut collaborate_player = Player::new("collaborate_player", &collaborate_strategy);
         assert_eq!(defect_player.score, 0);
         assert_eq!(collaborate_player.score, 0);
-        play_game(&mut defect_player, &mut collaborate_player, &TEST_PAYOFF);
+        play_game(
+            &mut defect_player,
+            &mut collaborate_player,
+            &TEST_PAYOFF,
+            0.0,
+        );
         assert_eq!(defect_player.score, 5);
         assert_eq!(
             *defect_player
@@ -154,20 +370,20 @@ mod tests {
     #[test]
     fn consecutive_games_played() {
         let defect_strategy = MockStrategy {
-            next_move: Cell::new(Move::Defect),
+            next_move: Mutex::new(Move::Defect),
         };
         let mut defect_player = Player::new("defect_player", &defect_strategy);
         let alternate_strategy = MockStrategy {
-            next_move: Cell::new(Move::Collaborate),
+            next_move: Mutex::new(Move::Collaborate),
         };
         let mut alternate_player = Player::new("alternate_player", &alternate_strategy);
         assert_eq!(defect_player.score, 0);
         assert_eq!(alternate_player.score, 0);
-        play_game(&mut defect_player, &mut alternate_player, &TEST_PAYOFF);
+        play_game(&mut defect_player, &mut alternate_player, &TEST_PAYOFF, 0.0);
         assert_eq!(defect_player.score, 5);
         assert_eq!(alternate_player.score, 0);
-        alternate_strategy.next_move.replace(Move::Defect);
-        play_game(&mut defect_player, &mut alternate_player, &TEST_PAYOFF);
+        *alternate_strategy.next_move.lock().unwrap() = Move::Defect;
+        play_game(&mut defect_player, &mut alternate_player, &TEST_PAYOFF, 0.0);
         assert_eq!(defect_player.score, 6);
         assert_eq!(
             *defect_player
@@ -207,11 +423,11 @@ mod tests {
     #[test]
     fn games_played() {
         let defect_strategy = MockStrategy {
-            next_move: Cell::new(Move::Defect),
+            next_move: Mutex::new(Move::Defect),
         };
         let mut defect_player = Player::new("defect_player", &defect_strategy);
         let collaborate_strategy = MockStrategy {
-            next_move: Cell::new(Move::Collaborate),
+            next_move: Mutex::new(Move::Collaborate),
         };
         let mut collaborate_player = Player::new("collaborate_player", &collaborate_strategy);
         assert_eq!(defect_player.score, 0);
@@ -235,5 +451,156 @@ mod tests {
                 .len(),
             9
         );
+        assert_eq!(defect_player.turns_played(), 9);
+    }
+
+    #[test]
+    fn noisy_games_flip_every_move() {
+        let defect_strategy = MockStrategy {
+            next_move: Mutex::new(Move::Defect),
+        };
+        let mut defect_player = Player::new("defect_player", &defect_strategy);
+        let collaborate_strategy = MockStrategy {
+            next_move: Mutex::new(Move::Collaborate),
+        };
+        let mut collaborate_player = Player::new("collaborate_player", &collaborate_strategy);
+        play_noisy_games(
+            &mut defect_player,
+            &mut collaborate_player,
+            &TEST_PAYOFF,
+            3,
+            1.0,
+        );
+        assert_eq!(defect_player.score(), 0);
+        assert_eq!(collaborate_player.score(), 15);
+    }
+
+    #[test]
+    fn match_leaves_players_untouched() {
+        let defect_strategy = MockStrategy {
+            next_move: Mutex::new(Move::Defect),
+        };
+        let defect_player = Player::new("defect_player", &defect_strategy);
+        let collaborate_strategy = MockStrategy {
+            next_move: Mutex::new(Move::Collaborate),
+        };
+        let collaborate_player = Player::new("collaborate_player", &collaborate_strategy);
+        let outcome = play_match(&defect_player, &collaborate_player, &TEST_PAYOFF, 4, 0.0);
+        assert_eq!(outcome.scores, (20, 0));
+        assert_eq!(
+            outcome.history,
+            vec![
+                GamePlay {
+                    my_move: Move::Defect,
+                    their_move: Move::Collaborate
+                };
+                4
+            ]
+        );
+        assert_eq!(defect_player.score(), 0);
+        assert_eq!(defect_player.turns_played(), 0);
+        assert_eq!(collaborate_player.score(), 0);
+    }
+
+    // TitForTat without its own alternating play.
+    struct Copycat {}
+
+    impl Strategy for Copycat {
+        fn play(&self, past_games: &[GamePlay]) -> Move {
+            past_games
+                .last()
+                .map_or(Move::Collaborate, |game| game.their_move)
+        }
+    }
+
+    #[test]
+    fn moves_pair_up_with_replies() {
+        let nasty = Nasty {};
+        let mut player = Player::new("player", &nasty);
+        player.moved("other", false, Move::Defect);
+        player.moved("other", true, Move::Collaborate);
+        player.moved("other", false, Move::Collaborate);
+        player.moved("other", true, Move::Defect);
+        player.moved("other", false, Move::Defect);
+        assert_eq!(
+            player.replies["other"],
+            vec![
+                GamePlay {
+                    my_move: Move::Collaborate,
+                    their_move: Move::Collaborate
+                },
+                GamePlay {
+                    my_move: Move::Defect,
+                    their_move: Move::Defect
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn second_mover_answers_latest_move_by_default() {
+        let (drunk, copycat) = (Drunk {}, Copycat {});
+        let first = Player::new("drunk", &drunk);
+        let second = Player::new("copycat", &copycat);
+        let alternating = play_alternating_match(&first, &second, &TEST_PAYOFF, 4, 0.0);
+        assert_eq!(alternating.scores, (8, 8));
+    }
+
+    #[test]
+    fn odd_repetitions_swap_first_mover() {
+        let (drunk, copycat) = (Drunk {}, Copycat {});
+        let first = Player::new("drunk", &drunk);
+        let second = Player::new("copycat", &copycat);
+        let even = play_alternating_repetition(&first, &second, &TEST_PAYOFF, 4, 0.0, 0);
+        assert_eq!(even.scores, (8, 8));
+        // Copycat opens by cooperating and then copies Drunk a turn late.
+        let odd = play_alternating_repetition(&first, &second, &TEST_PAYOFF, 4, 0.0, 1);
+        assert_eq!(odd.scores, (13, 8));
+        assert_eq!(
+            odd.history[1],
+            GamePlay {
+                my_move: Move::Defect,
+                their_move: Move::Collaborate
+            }
+        );
+        assert_eq!(odd.rounds[1].payoffs, (5, 0));
+    }
+
+    #[test]
+    fn second_mover_sees_first_move() {
+        let (drunk, tit_for_tat) = (Drunk {}, TitForTat {});
+        let first = Player::new("drunk", &drunk);
+        let second = Player::new("tit_for_tat", &tit_for_tat);
+        let alternating = play_alternating_match(&first, &second, &TEST_PAYOFF, 4, 0.0);
+        assert_eq!(alternating.scores, (8, 8));
+        assert!(alternating
+            .history
+            .iter()
+            .all(|game| game.my_move == game.their_move));
+        let simultaneous = play_match(&first, &second, &TEST_PAYOFF, 4, 0.0);
+        assert_eq!(simultaneous.scores, (13, 8));
+    }
+
+    #[test]
+    fn alternating_history_keeps_order() {
+        let (nasty, tit_for_tat) = (Nasty {}, TitForTat {});
+        let mut first = Player::new("tit_for_tat", &tit_for_tat);
+        let mut second = Player::new("nasty", &nasty);
+        play_alternating_game(&mut first, &mut second, &TEST_PAYOFF, 0.0);
+        assert_eq!(
+            second.past_moves["tit_for_tat"],
+            vec![
+                OrderedMove {
+                    mine: false,
+                    played: Move::Collaborate
+                },
+                OrderedMove {
+                    mine: true,
+                    played: Move::Defect
+                }
+            ]
+        );
+        assert_eq!(first.history("nasty").len(), 1);
+        assert_eq!((first.score(), second.score()), (0, 5));
     }
 }
@@ -1,32 +1,171 @@
 use crate::combinatorics;
 use crate::player::Player;
+use crate::progress::Monitor;
+use crate::rng;
 use crate::strategy::Strategy;
 use crate::tournament::Tournament;
+use rand::Rng;
 use std::collections::HashMap;
 
+mod cache;
+mod checkpoint;
+
+use cache::MatchCache;
+use checkpoint::Checkpoint;
+pub use checkpoint::{Checkpointing, SimulationError};
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulationOptions {
+    // Plays every pairing of deterministic strategies once and reuses its
+    // outcome in every composition. Ignored unless the tournament has neither
+    // noise nor a random match length.
+    pub cache_matches: bool,
+    pub checkpointing: Option<Checkpointing>,
+    // Reports every composition played. A cancelled simulation stops after
+    // the current composition, saving a checkpoint if it makes them.
+    pub monitor: Monitor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationOutcome {
+    pub strategy_wins: HashMap<String, u32>,
+    // Compositions played so far, resumed ones included, out of
+    // `compositions`.
+    pub completed: usize,
+    pub compositions: usize,
+}
+
+impl SimulationOutcome {
+    pub fn is_complete(&self) -> bool {
+        self.completed == self.compositions
+    }
+}
+
 pub fn simulate_populations(
     strategies: &[Box<dyn Strategy>],
     max_player_instances: u8,
     tournament: &Tournament,
 ) -> HashMap<String, u32> {
-    let mut strategy_wins = HashMap::new();
-    let all_combinations =
-        combinatorics::get_combinations(strategies.len().try_into().unwrap(), max_player_instances);
-    for player_counts in all_combinations {
+    simulate_populations_with(
+        strategies,
+        max_player_instances,
+        tournament,
+        &SimulationOptions::default(),
+    )
+    .expect("simulation without checkpoints doesn't fail")
+    .strategy_wins
+}
+
+// In a seeded tournament every composition is played with its own seed
+// derived from the tournament seed. Checkpointing draws a seed for unseeded
+// tournaments, so a resumed run ends exactly as an uninterrupted one.
+pub fn simulate_populations_with(
+    strategies: &[Box<dyn Strategy>],
+    max_player_instances: u8,
+    tournament: &Tournament,
+    options: &SimulationOptions,
+) -> Result<SimulationOutcome, SimulationError> {
+    let cache = (options.cache_matches && tournament.is_deterministic())
+        .then(|| MatchCache::new(strategies, tournament));
+    let seed = match (tournament.seed(), &options.checkpointing) {
+        (Some(seed), _) => Some(seed),
+        (None, Some(_)) => Some(rng::with_rng(|rng| rng.gen())),
+        (None, None) => None,
+    };
+    let names = strategies.iter().map(|strategy| strategy.name()).collect();
+    let mut checkpoint = Checkpoint::new(
+        names,
+        max_player_instances,
+        tournament.configuration(),
+        seed.unwrap_or(0),
+    );
+    if let Some(checkpointing) = options.checkpointing.as_ref().filter(|c| c.resume) {
+        if let Some(saved) = Checkpoint::load(&checkpointing.path)? {
+            // An unseeded tournament goes on with the seed of the saved run.
+            if tournament.seed().is_none() {
+                checkpoint.seed = saved.seed;
+            }
+            if !saved.matches(&checkpoint) {
+                return Err(SimulationError::CheckpointMismatch);
+            }
+            checkpoint = saved;
+        }
+    }
+    let seed = seed.map(|_| checkpoint.seed);
+    let bins = strategies.len().try_into().unwrap();
+    let compositions = combinatorics::combinations_count(bins, max_player_instances) as usize;
+    let tracker = options
+        .monitor
+        .track((compositions - checkpoint.next_composition) as u64);
+    let all_combinations = combinatorics::get_combinations(bins, max_player_instances);
+    for (index, player_counts) in all_combinations
+        .enumerate()
+        .skip(checkpoint.next_composition)
+    {
+        if tracker.is_cancelled() {
+            break;
+        }
+        let tournament = match seed {
+            Some(seed) => tournament.reseeded(rng::derive_seed(seed, index as u64)),
+            None => tournament.clone(),
+        };
         let mut players = create_players(strategies, &player_counts);
-        let winning_strategy = get_winning_strategy(&mut players, tournament);
-        strategy_wins
-            .entry(winning_strategy)
-            .and_modify(|counter| *counter += 1)
-            .or_insert(1);
+        let winners = match &cache {
+            Some(cache) => {
+                let strategy_of = player_strategies(&player_counts);
+                cache.winners(&players, &strategy_of, &tournament)
+            }
+            None => {
+                let result = tournament.play_games(&mut players);
+                tournament.winners(&result)
+            }
+        };
+        for winning_strategy in winning_strategies(&players, &winners) {
+            checkpoint
+                .strategy_wins
+                .entry(winning_strategy)
+                .and_modify(|counter| *counter += 1)
+                .or_insert(1);
+        }
+        checkpoint.next_composition = index + 1;
+        tracker.advance();
+        if let Some(checkpointing) = &options.checkpointing {
+            if checkpoint
+                .next_composition
+                .is_multiple_of(checkpointing.interval.max(1))
+            {
+                checkpoint.save(&checkpointing.path)?;
+            }
+        }
+    }
+    if let Some(checkpointing) = &options.checkpointing {
+        checkpoint.save(&checkpointing.path)?;
     }
-    strategy_wins
+    Ok(SimulationOutcome {
+        strategy_wins: checkpoint.strategy_wins,
+        completed: checkpoint.next_composition,
+        compositions,
+    })
 }
 
-fn get_winning_strategy(players: &mut [Player], tournament: &Tournament) -> String {
-    tournament.play_games(players);
-    let best_player = players.iter().max_by_key(|p| p.score());
-    best_player.expect("at least one player").strategy_name()
+// Strategies of the winners, each once. Only a shared tiebreak can make it
+// more than one.
+fn winning_strategies(players: &[Player], winners: &[usize]) -> Vec<String> {
+    let mut strategies: Vec<String> = winners
+        .iter()
+        .map(|winner| players[*winner].strategy_name())
+        .collect();
+    strategies.dedup();
+    strategies
+}
+
+// Index of the strategy of every player made by `create_players`.
+fn player_strategies(player_counts: &[u8]) -> Vec<usize> {
+    player_counts
+        .iter()
+        .enumerate()
+        .flat_map(|(strategy, count)| std::iter::repeat_n(strategy, (*count + 1).into()))
+        .collect()
 }
 
 fn create_players<'a>(
@@ -42,9 +181,7 @@ fn create_players<'a>(
         .iter()
         .zip(player_counts.iter())
         .flat_map(|(strategy, count)| {
-            std::iter::repeat(strategy)
-                .take((*count + 1).into())
-                .enumerate()
+            std::iter::repeat_n(strategy, (*count + 1).into()).enumerate()
         })
         .map(|(i, strategy)| -> Player {
             Player::new(&format!("{}_{}", strategy.name(), i), strategy.as_ref())
@@ -55,10 +192,17 @@ fn create_players<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::{Move, Timing};
+    use crate::player::GamePlay;
+    use crate::progress::CancellationToken;
+    use crate::strategy::drunk::Drunk;
     use crate::strategy::naive::Naive;
     use crate::strategy::nasty::Nasty;
     use crate::strategy::tit_for_tat::TitForTat;
     use crate::strategy::Strategy;
+    use crate::tournament::{ComplexityCost, ScoringMode, Tiebreak};
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn naive_nasty() {
@@ -84,4 +228,236 @@ mod tests {
         assert_eq!(*strategy_wins.get("Nasty").unwrap(), 52);
         assert_eq!(*strategy_wins.get("TitForTat").unwrap(), 12);
     }
+
+    #[test]
+    fn shared_wins_count_every_tied_strategy() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+        ];
+        let tournament = Tournament::builder()
+            .tiebreak(Tiebreak::Shared)
+            .build()
+            .unwrap();
+        let strategy_wins =
+            simulate_populations(&strategies, /* max_player_instances= */ 4, &tournament);
+        assert_eq!(*strategy_wins.get("Nasty").unwrap(), 53);
+        assert_eq!(*strategy_wins.get("TitForTat").unwrap(), 12);
+    }
+
+    #[test]
+    fn cached_matches_give_same_wins() {
+        let strategies = crate::strategy::all_strategies();
+        let tournaments = [
+            Tournament::builder().seed(4).build().unwrap(),
+            Tournament::builder()
+                .self_play(true)
+                .repetitions(2)
+                .seed(5)
+                .build()
+                .unwrap(),
+            Tournament::builder()
+                .scoring(ScoringMode::WinDrawLoss)
+                .tiebreak(Tiebreak::Shared)
+                .seed(6)
+                .build()
+                .unwrap(),
+            Tournament::builder()
+                .self_play(true)
+                .scoring(ScoringMode::PayoffPerTurn)
+                .tiebreak(Tiebreak::Random)
+                .seed(7)
+                .build()
+                .unwrap(),
+        ];
+        let cached = SimulationOptions {
+            cache_matches: true,
+            ..SimulationOptions::default()
+        };
+        for tournament in &tournaments {
+            assert_eq!(
+                simulate_populations_with(&strategies, 2, tournament, &cached)
+                    .unwrap()
+                    .strategy_wins,
+                simulate_populations(&strategies, 2, tournament)
+            );
+        }
+    }
+
+    #[test]
+    fn alternating_matches_give_same_wins_with_cache() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(TitForTat {}), Box::new(Drunk {})];
+        let tournament = Tournament::builder()
+            .timing(Timing::Alternating)
+            .match_length(5)
+            .tiebreak(Tiebreak::Shared)
+            .build()
+            .unwrap();
+        let cached = SimulationOptions {
+            cache_matches: true,
+            ..SimulationOptions::default()
+        };
+        let wins = simulate_populations(&strategies, 3, &tournament);
+        assert_eq!(wins.get("Drunk"), Some(&1));
+        assert_eq!(
+            simulate_populations_with(&strategies, 3, &tournament, &cached)
+                .unwrap()
+                .strategy_wins,
+            wins
+        );
+    }
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()))
+    }
+
+    fn checkpointing(path: &Path, resume: bool) -> SimulationOptions {
+        SimulationOptions {
+            checkpointing: Some(Checkpointing {
+                path: path.to_path_buf(),
+                interval: 2,
+                resume,
+            }),
+            ..SimulationOptions::default()
+        }
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let tournament = Tournament::all_pairs();
+        let path = checkpoint_path("resumes_from_checkpoint");
+        // Pretend Naive won the first two of the four compositions.
+        let names = vec!["Naive".into(), "Nasty".into()];
+        let mut saved = Checkpoint::new(names, 2, tournament.configuration(), 0);
+        saved.next_composition = 2;
+        saved.strategy_wins.insert("Naive".to_string(), 2);
+        saved.save(&path).unwrap();
+        let strategy_wins =
+            simulate_populations_with(&strategies, 2, &tournament, &checkpointing(&path, true))
+                .unwrap()
+                .strategy_wins;
+        assert_eq!(strategy_wins.get("Naive"), Some(&2));
+        assert_eq!(strategy_wins.get("Nasty"), Some(&2));
+        let finished = Checkpoint::load(&path).unwrap().unwrap();
+        assert_eq!(finished.next_composition, 4);
+        let strategy_wins =
+            simulate_populations_with(&strategies, 3, &tournament, &checkpointing(&path, true));
+        assert!(matches!(
+            strategy_wins,
+            Err(SimulationError::CheckpointMismatch)
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_checkpoint_of_other_tournament() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let path = checkpoint_path("rejects_checkpoint_of_other_tournament");
+        let _ = fs::remove_file(&path);
+        let tournament = Tournament::builder().seed(1).build().unwrap();
+        simulate_populations_with(&strategies, 2, &tournament, &checkpointing(&path, false))
+            .unwrap();
+        let others = [
+            Tournament::builder().seed(1).self_play(true).build(),
+            Tournament::builder().seed(1).noise(0.1).build(),
+            Tournament::builder().seed(1).match_length(20).build(),
+            Tournament::builder()
+                .seed(1)
+                .scoring(ScoringMode::WinDrawLoss)
+                .build(),
+            Tournament::builder()
+                .seed(1)
+                .tiebreak(Tiebreak::Shared)
+                .build(),
+            Tournament::builder()
+                .seed(1)
+                .timing(Timing::Alternating)
+                .build(),
+            Tournament::builder()
+                .seed(1)
+                .complexity_cost(ComplexityCost {
+                    per_match: 1.0,
+                    ..ComplexityCost::default()
+                })
+                .build(),
+        ];
+        for other in others {
+            let resumed = simulate_populations_with(
+                &strategies,
+                2,
+                &other.unwrap(),
+                &checkpointing(&path, true),
+            );
+            assert!(matches!(resumed, Err(SimulationError::CheckpointMismatch)));
+        }
+        assert!(simulate_populations_with(
+            &strategies,
+            2,
+            &tournament,
+            &checkpointing(&path, true)
+        )
+        .is_ok());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn checkpoint_holds_random_state() {
+        let strategies = crate::strategy::all_strategies();
+        let path = checkpoint_path("checkpoint_holds_random_state");
+        let _ = fs::remove_file(&path);
+        let unseeded = simulate_populations_with(
+            &strategies,
+            2,
+            &Tournament::all_pairs(),
+            &checkpointing(&path, false),
+        )
+        .unwrap()
+        .strategy_wins;
+        let saved = Checkpoint::load(&path).unwrap().unwrap();
+        assert_eq!(saved.strategy_wins, unseeded);
+        let seeded = Tournament::builder().seed(saved.seed).build().unwrap();
+        assert_eq!(simulate_populations(&strategies, 2, &seeded), unseeded);
+        fs::remove_file(&path).unwrap();
+    }
+
+    // Cooperates, cancelling the token as soon as it plays.
+    struct Interrupter(CancellationToken);
+
+    impl Strategy for Interrupter {
+        fn play(&self, _past_games: &[GamePlay]) -> Move {
+            self.0.cancel();
+            Move::Collaborate
+        }
+    }
+
+    #[test]
+    fn cancelled_run_resumes_to_same_result() {
+        let token = CancellationToken::default();
+        let mut strategies = crate::strategy::all_strategies();
+        strategies.push(Box::new(Interrupter(token.clone())));
+        let tournament = Tournament::builder().seed(9).build().unwrap();
+        let path = checkpoint_path("cancelled_run_resumes_to_same_result");
+        let _ = fs::remove_file(&path);
+        let mut options = checkpointing(&path, true);
+        options.monitor = Monitor {
+            progress: None,
+            cancellation: Some(token),
+        };
+        // The first composition cancels the run, which stops before the next.
+        let partial = simulate_populations_with(&strategies, 2, &tournament, &options).unwrap();
+        assert!(options.monitor.is_cancelled());
+        assert_eq!((partial.completed, partial.compositions), (1, 128));
+        assert!(!partial.is_complete());
+        let resumed =
+            simulate_populations_with(&strategies, 2, &tournament, &checkpointing(&path, true))
+                .unwrap();
+        assert!(resumed.is_complete());
+        assert_eq!(
+            resumed.strategy_wins,
+            simulate_populations(&strategies, 2, &tournament)
+        );
+        fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,128 @@
+use crate::player::Player;
+use crate::strategy::Strategy;
+use crate::tournament::{match_points, Tournament};
+use rayon::prelude::*;
+
+// Outcome of a match for its first player.
+#[derive(Debug, Clone, Copy)]
+struct CachedMatch {
+    score: u64,
+    turns: u64,
+    points: u32,
+}
+
+// Outcomes of the matches between every pair of deterministic strategies of a
+// deterministic tournament. Every population score is then a sum of cached
+// outcomes weighted by the number of opponents with each strategy, and only
+// the matches involving a nondeterministic strategy are still played.
+pub(super) struct MatchCache {
+    // `outcomes[a][b]` is None unless both strategies are deterministic.
+    outcomes: Vec<Vec<Option<CachedMatch>>>,
+}
+
+impl MatchCache {
+    pub(super) fn new(strategies: &[Box<dyn Strategy>], tournament: &Tournament) -> Self {
+        assert!(
+            tournament.is_deterministic(),
+            "only deterministic tournaments can be cached"
+        );
+        let outcomes = strategies
+            .iter()
+            .map(|first| {
+                strategies
+                    .iter()
+                    .map(|second| {
+                        if !first.is_deterministic() || !second.is_deterministic() {
+                            return None;
+                        }
+                        let players = [
+                            Player::new("first", first.as_ref()),
+                            Player::new("second", second.as_ref()),
+                        ];
+                        let result = tournament.play_match(&players, 0, 0, (0, 1));
+                        Some(CachedMatch {
+                            score: result.scores.0,
+                            turns: result.turns,
+                            points: match_points(result.scores).0,
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { outcomes }
+    }
+
+    // Same as `Tournament::winners` of `Tournament::play_games`, where
+    // `strategy_of[i]` is the index of the strategy of player `i`.
+    pub(super) fn winners(
+        &self,
+        players: &[Player],
+        strategy_of: &[usize],
+        tournament: &Tournament,
+    ) -> Vec<usize> {
+        let mut instances = vec![0; self.outcomes.len()];
+        for strategy in strategy_of {
+            instances[*strategy] += 1;
+        }
+        let repetitions = tournament.repetitions();
+        let mut totals = vec![0; players.len()];
+        let mut turns = vec![0; players.len()];
+        let mut points = vec![0; players.len()];
+        let mut matches_played = vec![0; players.len()];
+        for (player, first) in strategy_of.iter().enumerate() {
+            for (second, outcome) in self.outcomes[*first].iter().enumerate() {
+                let Some(outcome) = outcome else {
+                    continue;
+                };
+                let mut matches = instances[second] - u32::from(*first == second);
+                points[player] += repetitions * matches * outcome.points;
+                if *first == second && tournament.play_twin() {
+                    matches += 1;
+                }
+                let played = u64::from(repetitions * matches);
+                totals[player] += played * outcome.score;
+                turns[player] += played * outcome.turns;
+                matches_played[player] += repetitions * matches;
+            }
+        }
+        // The remaining matches keep their index in the schedule, so a seeded
+        // tournament plays them exactly as without the cache.
+        let uncached: Vec<_> = tournament
+            .schedule(players.len())
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, (i, j)))| self.outcomes[strategy_of[*i]][strategy_of[*j]].is_none())
+            .collect();
+        let matches: Vec<_> = uncached
+            .into_par_iter()
+            .map(|(index, (repetition, pair))| {
+                tournament.play_match(players, index as u64, repetition, pair)
+            })
+            .collect();
+        for result in matches {
+            let (first, second) = result.players;
+            totals[first] += result.scores.0;
+            turns[first] += result.turns;
+            matches_played[first] += 1;
+            if !result.is_self_play() {
+                totals[second] += result.scores.1;
+                turns[second] += result.turns;
+                matches_played[second] += 1;
+                let (first_points, second_points) = match_points(result.scores);
+                points[first] += first_points;
+                points[second] += second_points;
+            }
+        }
+        let cost = tournament.complexity_cost();
+        let costs: Vec<f64> = players
+            .iter()
+            .enumerate()
+            .map(|(player, instance)| {
+                cost.per_turn(instance.strategy().complexity()) * turns[player] as f64
+                    + cost.per_match * f64::from(matches_played[player])
+            })
+            .collect();
+        let scores = tournament.scores_from_tallies(&totals, &turns, &costs, || points);
+        tournament.winners_by_score(&scores, &totals)
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+const CHECKPOINT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpointing {
+    pub path: PathBuf,
+    // Number of compositions played between two checkpoints.
+    pub interval: usize,
+    // Continue from the checkpoint at `path` if there is one.
+    pub resume: bool,
+}
+
+// Progress of a population simulation. Composition `i` is played by the
+// tournament reseeded with `derive_seed(seed, i)`, so the seed and the index
+// of the next composition stand for the whole random state. The other
+// tournament settings are kept as `Tournament::configuration`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct Checkpoint {
+    pub version: u32,
+    pub strategies: Vec<String>,
+    pub max_player_instances: u8,
+    // Missing from checkpoints of older versions, which are rejected anyway.
+    #[serde(default)]
+    pub tournament: String,
+    pub seed: u64,
+    pub next_composition: usize,
+    pub strategy_wins: HashMap<String, u32>,
+}
+
+#[derive(Debug)]
+pub enum SimulationError {
+    Io(io::Error),
+    InvalidCheckpoint(serde_json::Error),
+    // The checkpoint belongs to a simulation with other settings.
+    CheckpointMismatch,
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulationError::Io(error) => write!(f, "checkpoint I/O failed: {}", error),
+            SimulationError::InvalidCheckpoint(error) => {
+                write!(f, "checkpoint is invalid: {}", error)
+            }
+            SimulationError::CheckpointMismatch => {
+                write!(f, "checkpoint belongs to a different simulation")
+            }
+        }
+    }
+}
+
+impl Error for SimulationError {}
+
+impl From<io::Error> for SimulationError {
+    fn from(error: io::Error) -> Self {
+        SimulationError::Io(error)
+    }
+}
+
+impl Checkpoint {
+    pub(super) fn new(
+        strategies: Vec<String>,
+        max_player_instances: u8,
+        tournament: String,
+        seed: u64,
+    ) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            strategies,
+            max_player_instances,
+            tournament,
+            seed,
+            next_composition: 0,
+            strategy_wins: HashMap::new(),
+        }
+    }
+
+    // The checkpoint at `path`, or None if there is no file.
+    pub(super) fn load(path: &Path) -> Result<Option<Self>, SimulationError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let checkpoint: Self =
+            serde_json::from_str(&text).map_err(SimulationError::InvalidCheckpoint)?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(SimulationError::CheckpointMismatch);
+        }
+        Ok(Some(checkpoint))
+    }
+
+    // Writes to a temporary file first, so an interrupted save leaves the
+    // previous checkpoint intact.
+    pub(super) fn save(&self, path: &Path) -> Result<(), SimulationError> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let json = serde_json::to_string_pretty(self).expect("serializable checkpoint");
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    pub(super) fn matches(&self, other: &Checkpoint) -> bool {
+        self.strategies == other.strategies
+            && self.max_player_instances == other.max_player_instances
+            && self.tournament == other.tournament
+            && self.seed == other.seed
+    }
+}
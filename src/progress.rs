@@ -0,0 +1,187 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Reports are sent at most this often, except for the last one.
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+// Cancels a run from any thread. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub completed: u64,
+    pub total: u64,
+    pub elapsed: Duration,
+    // Remaining time if the rest goes as fast as what is completed.
+    pub eta: Option<Duration>,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = match self.total {
+            0 => 100.0,
+            total => 100.0 * self.completed as f64 / total as f64,
+        };
+        write!(f, "{}/{} ({:.1}%)", self.completed, self.total, percent)?;
+        match self.eta {
+            Some(eta) => write!(f, ", ETA {}s", eta.as_secs()),
+            None => write!(f, ", ETA unknown"),
+        }
+    }
+}
+
+// How a long run reports its progress and learns that it should stop. A run
+// that is cancelled returns what it completed so far.
+#[derive(Debug, Clone, Default)]
+pub struct Monitor {
+    pub progress: Option<Sender<Progress>>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Monitor {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    pub(crate) fn track(&self, total: u64) -> Tracker<'_> {
+        Tracker {
+            monitor: self,
+            total,
+            start: Instant::now(),
+            state: Mutex::new(TrackerState::default()),
+        }
+    }
+}
+
+// Counts the completed steps of one run, possibly from several threads.
+// Steps are counted and reported under one lock, so the reports never go
+// backwards.
+pub(crate) struct Tracker<'a> {
+    monitor: &'a Monitor,
+    total: u64,
+    start: Instant,
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    completed: u64,
+    last_report: Option<Instant>,
+}
+
+impl Tracker<'_> {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.monitor.is_cancelled()
+    }
+
+    pub(crate) fn advance(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.completed += 1;
+        let completed = state.completed;
+        let Some(sender) = &self.monitor.progress else {
+            return;
+        };
+        let now = Instant::now();
+        let due = state
+            .last_report
+            .is_none_or(|last| now - last >= REPORT_INTERVAL);
+        if !due && completed < self.total {
+            return;
+        }
+        state.last_report = Some(now);
+        let elapsed = now - self.start;
+        let eta = (completed > 0).then(|| {
+            elapsed.mul_f64(self.total.saturating_sub(completed) as f64 / completed as f64)
+        });
+        // Nobody listening any more is not a reason to stop the run.
+        let _ = sender.send(Progress {
+            completed,
+            total: self.total,
+            elapsed,
+            eta,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_first_and_last_step() {
+        let (sender, receiver) = mpsc::channel();
+        let monitor = Monitor {
+            progress: Some(sender),
+            cancellation: None,
+        };
+        {
+            let tracker = monitor.track(3);
+            for _ in 0..3 {
+                tracker.advance();
+            }
+        }
+        drop(monitor);
+        let reports: Vec<_> = receiver.iter().collect();
+        assert_eq!(reports.first().unwrap().completed, 1);
+        assert_eq!(reports.last().unwrap().completed, 3);
+        assert_eq!(reports.last().unwrap().eta, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn parallel_reports_never_go_backwards() {
+        let (sender, receiver) = mpsc::channel();
+        let monitor = Monitor {
+            progress: Some(sender),
+            cancellation: None,
+        };
+        {
+            let tracker = monitor.track(10_000);
+            (0..10_000).into_par_iter().for_each(|_| tracker.advance());
+        }
+        drop(monitor);
+        let completed: Vec<_> = receiver.iter().map(|report| report.completed).collect();
+        assert!(completed.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(completed.last(), Some(&10_000));
+    }
+
+    #[test]
+    fn cancellation_is_shared() {
+        let token = CancellationToken::default();
+        let monitor = Monitor {
+            progress: None,
+            cancellation: Some(token.clone()),
+        };
+        assert!(!monitor.is_cancelled());
+        token.cancel();
+        assert!(monitor.is_cancelled());
+    }
+
+    #[test]
+    fn display() {
+        let progress = Progress {
+            completed: 1,
+            total: 4,
+            elapsed: Duration::from_secs(2),
+            eta: Some(Duration::from_secs(6)),
+        };
+        assert_eq!(progress.to_string(), "1/4 (25.0%), ETA 6s");
+    }
+}
@@ -0,0 +1,71 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+// Every random decision taken while playing (noisy moves, random strategies,
+// random match lengths) goes through this per-thread generator so that a
+// seeded run can be reproduced exactly.
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+// Seed of an independent stream of random numbers derived from `seed`, so
+// that e.g. every match of a tournament can be seeded on its own and played
+// in any order or thread.
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    split_mix(seed ^ split_mix(stream))
+}
+
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+pub fn random_bool(probability: f64) -> bool {
+    with_rng(|rng| rng.gen_bool(probability))
+}
+
+pub fn random_f64() -> f64 {
+    with_rng(|rng| rng.gen())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        seed(42);
+        let first: Vec<f64> = (0..10).map(|_| random_f64()).collect();
+        seed(42);
+        let second: Vec<f64> = (0..10).map(|_| random_f64()).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn different_seed_different_sequence() {
+        seed(1);
+        let first: Vec<f64> = (0..10).map(|_| random_f64()).collect();
+        seed(2);
+        let second: Vec<f64> = (0..10).map(|_| random_f64()).collect();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn derived_seeds_differ() {
+        assert_eq!(derive_seed(1, 2), derive_seed(1, 2));
+        assert_ne!(derive_seed(1, 2), derive_seed(1, 3));
+        assert_ne!(derive_seed(1, 2), derive_seed(2, 1));
+        assert_ne!(derive_seed(0, 1), derive_seed(1, 0));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::rng;
+use rand::Rng;
+
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+// Descriptive statistics of a set of samples. The confidence interval is a
+// percentile bootstrap interval of the mean.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub confidence_interval: (f64, f64),
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        assert!(!samples.is_empty(), "at least one sample");
+        let mean = mean(samples);
+        Self {
+            mean,
+            median: median(samples),
+            std_dev: std_dev(samples, mean),
+            min: samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max: samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            confidence_interval: bootstrap_interval(samples),
+        }
+    }
+}
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+pub fn median(samples: &[f64]) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+// Sample standard deviation, zero for a single sample.
+fn std_dev(samples: &[f64], mean: f64) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let squares: f64 = samples.iter().map(|sample| (sample - mean).powi(2)).sum();
+    (squares / (samples.len() - 1) as f64).sqrt()
+}
+
+fn bootstrap_interval(samples: &[f64]) -> (f64, f64) {
+    let mut means: Vec<f64> = rng::with_rng(|rng| {
+        (0..BOOTSTRAP_RESAMPLES)
+            .map(|_| {
+                let total: f64 = (0..samples.len())
+                    .map(|_| samples[rng.gen_range(0..samples.len())])
+                    .sum();
+                total / samples.len() as f64
+            })
+            .collect()
+    });
+    means.sort_by(f64::total_cmp);
+    let tail = (1.0 - CONFIDENCE_LEVEL) / 2.0;
+    let lower = (tail * BOOTSTRAP_RESAMPLES as f64) as usize;
+    let upper = ((1.0 - tail) * BOOTSTRAP_RESAMPLES as f64) as usize;
+    (means[lower], means[upper.min(BOOTSTRAP_RESAMPLES - 1)])
+}
+
+// Rank of every score, 1 being the highest. Tied scores share the average of
+// the ranks they span.
+pub fn ranks(scores: &[f64]) -> Vec<f64> {
+    scores
+        .iter()
+        .map(|score| {
+            let higher = scores.iter().filter(|other| *other > score).count();
+            let equal = scores.iter().filter(|other| *other == score).count();
+            higher as f64 + (equal + 1) as f64 / 2.0
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let summary = Summary::from_samples(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert!((summary.std_dev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        let (lower, upper) = summary.confidence_interval;
+        assert!((1.0..=2.5).contains(&lower));
+        assert!((2.5..=4.0).contains(&upper));
+    }
+
+    #[test]
+    fn constant_samples() {
+        let summary = Summary::from_samples(&[7.0; 5]);
+        assert_eq!(summary.median, 7.0);
+        assert_eq!(summary.std_dev, 0.0);
+        assert_eq!(summary.confidence_interval, (7.0, 7.0));
+    }
+
+    #[test]
+    fn single_sample() {
+        let summary = Summary::from_samples(&[3.0]);
+        assert_eq!(summary.mean, 3.0);
+        assert_eq!(summary.std_dev, 0.0);
+    }
+
+    #[test]
+    fn odd_median() {
+        assert_eq!(median(&[5.0, 1.0, 3.0]), 3.0);
+    }
+
+    #[test]
+    fn ranks_with_ties() {
+        assert_eq!(ranks(&[10.0, 30.0, 10.0, 20.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+}
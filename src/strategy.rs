@@ -1,5 +1,5 @@
 use crate::game::Move;
-use crate::player::GamePlay;
+use crate::player::{GamePlay, OrderedMove};
 
 pub mod drunk;
 pub mod grim_trigger;
@@ -8,15 +8,48 @@ pub mod nasty;
 pub mod random;
 pub mod tit_for_tat;
 
-pub trait Strategy {
+// What a strategy has to remember to play, which complexity costs charge for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Complexity {
+    // Number of past turns the next move depends on.
+    MemoryDepth(u32),
+    // Number of states of the finite state machine playing the strategy.
+    States(u32),
+}
+
+// Strategies are shared between the threads playing a tournament.
+pub trait Strategy: Send + Sync {
     fn play(&self, past_games: &[GamePlay]) -> Move;
     fn name(&self) -> String {
-        format!("{}", std::any::type_name::<Self>())
+        std::any::type_name::<Self>()
             .split("::")
             .last()
             .unwrap()
             .to_string()
     }
+    // Move in an alternating game, given every move of the player paired
+    // with the opponent's reply to it, and every move so far in the order
+    // played. Unless overridden, the strategy plays as in the simultaneous
+    // game on the pairs, which end with the opponent's latest move.
+    fn play_alternating(&self, replies: &[GamePlay], _past_moves: &[OrderedMove]) -> Move {
+        self.play(replies)
+    }
+    // Whether the move only depends on the past games. Outcomes of matches
+    // between deterministic strategies can be reused, so unless overridden a
+    // strategy is taken to be random.
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+    // Summary of the past games that every later move depends on, or None if
+    // unknown. Histories with equal states must lead to equal moves against
+    // equal opponent moves, which lets long matches skip repeating cycles.
+    fn state(&self, _past_games: &[GamePlay]) -> Option<u64> {
+        None
+    }
+    // Unless overridden, the strategy is taken to remember nothing.
+    fn complexity(&self) -> Complexity {
+        Complexity::MemoryDepth(0)
+    }
 }
 
 pub fn all_strategies() -> Vec<Box<dyn Strategy>> {
@@ -29,3 +62,9 @@ pub fn all_strategies() -> Vec<Box<dyn Strategy>> {
         Box::new(random::Random {}),
     ]
 }
+
+pub fn find_strategy(name: &str) -> Option<Box<dyn Strategy>> {
+    all_strategies()
+        .into_iter()
+        .find(|strategy| strategy.name().eq_ignore_ascii_case(name))
+}
@@ -1,6 +1,6 @@
 use crate::game::Move;
 use crate::player::GamePlay;
-use crate::strategy::Strategy;
+use crate::strategy::{Complexity, Strategy};
 
 pub struct Drunk {}
 
@@ -11,4 +11,19 @@ impl Strategy for Drunk {
             Some(last_game) => last_game.my_move.oposite_move(),
         }
     }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn state(&self, past_games: &[GamePlay]) -> Option<u64> {
+        Some(match past_games.last() {
+            None => 0,
+            Some(last_game) => 1 + last_game.my_move as u64,
+        })
+    }
+
+    fn complexity(&self) -> Complexity {
+        Complexity::MemoryDepth(1)
+    }
 }
@@ -1,6 +1,6 @@
 use crate::game::Move;
-use crate::player::GamePlay;
-use crate::strategy::Strategy;
+use crate::player::{GamePlay, OrderedMove};
+use crate::strategy::{Complexity, Strategy};
 
 pub struct GrimTrigger {}
 
@@ -13,4 +13,28 @@ impl Strategy for GrimTrigger {
         }
         Move::Collaborate
     }
+
+    fn play_alternating(&self, _replies: &[GamePlay], past_moves: &[OrderedMove]) -> Move {
+        if past_moves
+            .iter()
+            .any(|ordered| !ordered.mine && ordered.played == Move::Defect)
+        {
+            Move::Defect
+        } else {
+            Move::Collaborate
+        }
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn state(&self, past_games: &[GamePlay]) -> Option<u64> {
+        Some(u64::from(self.play(past_games) == Move::Defect))
+    }
+
+    // Cooperating until triggered, then defecting forever.
+    fn complexity(&self) -> Complexity {
+        Complexity::States(2)
+    }
 }
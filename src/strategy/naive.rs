@@ -8,4 +8,12 @@ impl Strategy for Naive {
     fn play(&self, _past_games: &[GamePlay]) -> Move {
         Move::Collaborate
     }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn state(&self, _past_games: &[GamePlay]) -> Option<u64> {
+        Some(0)
+    }
 }
@@ -8,4 +8,12 @@ impl Strategy for Nasty {
     fn play(&self, _past_games: &[GamePlay]) -> Move {
         Move::Defect
     }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn state(&self, _past_games: &[GamePlay]) -> Option<u64> {
+        Some(0)
+    }
 }
@@ -1,14 +1,19 @@
 use crate::game::Move;
 use crate::player::GamePlay;
+use crate::rng;
 use crate::strategy::Strategy;
 
 pub struct Random {}
 
 impl Strategy for Random {
     fn play(&self, _past_games: &[GamePlay]) -> Move {
-        match rand::random::<bool>() {
+        match rng::random_bool(0.5) {
             true => Move::Collaborate,
             false => Move::Defect,
         }
     }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
 }
@@ -1,6 +1,6 @@
 use crate::game::Move;
-use crate::player::GamePlay;
-use crate::strategy::Strategy;
+use crate::player::{GamePlay, OrderedMove};
+use crate::strategy::{Complexity, Strategy};
 
 pub struct TitForTat {}
 
@@ -11,4 +11,27 @@ impl Strategy for TitForTat {
             Some(last_game) => last_game.their_move,
         }
     }
+
+    // Answers the opponent's latest move, even when it was made this round.
+    fn play_alternating(&self, _replies: &[GamePlay], past_moves: &[OrderedMove]) -> Move {
+        match past_moves.iter().rev().find(|ordered| !ordered.mine) {
+            None => Move::Collaborate,
+            Some(their_move) => their_move.played,
+        }
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn state(&self, past_games: &[GamePlay]) -> Option<u64> {
+        Some(match past_games.last() {
+            None => 0,
+            Some(last_game) => 1 + last_game.their_move as u64,
+        })
+    }
+
+    fn complexity(&self) -> Complexity {
+        Complexity::MemoryDepth(1)
+    }
 }
@@ -1,50 +1,499 @@
-use crate::game::Payoff;
+use crate::cycle::{self, MatchMode};
+use crate::game::{Move, Payoff, Timing};
 use crate::player::{self, Player};
+use crate::progress::Monitor;
+use crate::rng;
+use rand::Rng;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+mod builder;
+mod cost;
+mod fault;
+mod knockout;
+mod probe;
+mod ranking;
+mod repeated;
+mod result;
+mod spatial;
+mod swiss;
+
+pub use builder::{TournamentBuilder, TournamentError};
+pub use cost::ComplexityCost;
+pub use fault::{Fault, FaultPolicy, Incident};
+pub use knockout::{seeding, Bracket, BracketMatch, DrawTiebreak, Elimination, Section};
+pub use probe::{OpponentReport, ProbePanel, ProbeReport, VariantReport};
+pub use ranking::{RankedPlayer, Ranking};
+pub use repeated::{RepeatedTournamentReport, StrategyReport};
+pub use result::{MatchResult, TournamentResult};
+pub use swiss::{SwissResult, SwissRound, SwissStanding};
 
 const DEFAULT_ITERATIONS: u32 = 10;
+// Random stream of the tiebreak, apart from the ones of the matches.
+const TIEBREAK_STREAM: u64 = u64::MAX - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchLength {
+    Fixed(u32),
+    // Number of turns drawn uniformly from `min..=max` for every match.
+    Uniform { min: u32, max: u32 },
+    // After every turn the match goes on with the given probability, as in
+    // Axelrod's second tournament.
+    Geometric { continuation_probability: f64 },
+}
+
+impl MatchLength {
+    fn sample(&self) -> u32 {
+        match *self {
+            MatchLength::Fixed(turns) => turns,
+            MatchLength::Uniform { min, max } => rng::with_rng(|rng| rng.gen_range(min..=max)),
+            MatchLength::Geometric {
+                continuation_probability,
+            } => {
+                let mut turns = 1;
+                while rng::random_bool(continuation_probability) {
+                    turns += 1;
+                }
+                turns
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoringMode {
+    TotalPayoff,
+    PayoffPerTurn,
+    // League table points: 3 for outscoring the opponent in a match, 1 for a
+    // draw and 0 for a loss. Matches against twins don't count.
+    WinDrawLoss,
+}
+
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
 
+// How `Tournament::winners` settles a tie for the best score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tiebreak {
+    // The tied player listed last wins, as before tiebreaks could be chosen.
+    LastPlayer,
+    // The tied player listed first wins.
+    FirstPlayer,
+    // The tied player with the highest total payoff wins, then the first one.
+    TotalPayoff,
+    // A tied player drawn at random, from the tournament seed if it has one.
+    Random,
+    // Every tied player wins.
+    Shared,
+}
+
+#[derive(Debug, Clone)]
 pub struct Tournament {
     payoff: Payoff,
-    iterations: u32,
+    match_length: MatchLength,
     play_twin: bool,
+    noise: f64,
+    repetitions: u32,
+    seed: Option<u64>,
+    scoring: ScoringMode,
+    tiebreak: Tiebreak,
+    match_mode: MatchMode,
+    record_moves: bool,
+    timing: Timing,
+    move_budget: Option<Duration>,
+    match_budget: Option<Duration>,
+    fault_policy: FaultPolicy,
+    complexity_cost: ComplexityCost,
 }
 
 impl Tournament {
+    pub fn builder() -> TournamentBuilder {
+        TournamentBuilder::default()
+    }
+
     pub fn all_pairs() -> Self {
-        Self {
-            payoff: Payoff::default(),
-            iterations: DEFAULT_ITERATIONS,
-            play_twin: false,
-        }
+        Self::builder()
+            .build()
+            .expect("default tournament is valid")
     }
 
     pub fn axelrod_tournament() -> Self {
-        Self {
-            payoff: Payoff::default(),
-            iterations: DEFAULT_ITERATIONS,
-            play_twin: true,
+        Self::builder()
+            .self_play(true)
+            .build()
+            .expect("default tournament is valid")
+    }
+
+    // Plays every match of the tournament in parallel and adds the scores to
+    // the players once all of them are done. Every match starts from fresh
+    // copies of its players and, in a seeded tournament, from its own seed, so
+    // the result doesn't depend on the number of threads.
+    pub fn play_games(&self, players: &mut [Player]) -> TournamentResult {
+        self.play_games_monitored(players, &Monitor::default())
+    }
+
+    // Like `play_games`, reporting every match played. Once cancelled, the
+    // matches not started yet are skipped and the result only holds the
+    // others.
+    pub fn play_games_monitored(
+        &self,
+        players: &mut [Player],
+        monitor: &Monitor,
+    ) -> TournamentResult {
+        let schedule = self.schedule(players.len());
+        self.play_schedule(players, schedule, monitor)
+    }
+
+    fn play_schedule(
+        &self,
+        players: &mut [Player],
+        schedule: Vec<(u32, (usize, usize))>,
+        monitor: &Monitor,
+    ) -> TournamentResult {
+        let tracker = monitor.track(schedule.len() as u64);
+        let matches: Vec<_> = schedule
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, (repetition, pair))| {
+                if tracker.is_cancelled() {
+                    return None;
+                }
+                let result = self.play_match(players, index as u64, repetition, pair);
+                tracker.advance();
+                Some(result)
+            })
+            .collect();
+        let mut result = TournamentResult::new(players);
+        result.turn_costs = players
+            .iter()
+            .map(|player| {
+                self.complexity_cost
+                    .per_turn(player.strategy().complexity())
+            })
+            .collect();
+        result.match_cost = self.complexity_cost.per_match;
+        result.cancelled = matches.iter().any(Option::is_none);
+        let matches: Vec<_> = matches.into_iter().flatten().collect();
+        if self.fault_policy == FaultPolicy::Disqualify {
+            let disqualified: BTreeSet<usize> = matches
+                .iter()
+                .flat_map(|result| &result.incidents)
+                .map(|incident| incident.player)
+                .collect();
+            result.disqualified = disqualified.into_iter().collect();
+        }
+        for mut match_result in matches {
+            let (first, second) = match_result.players;
+            if result.disqualified.contains(&first) || result.disqualified.contains(&second) {
+                match_result.void();
+            }
+            result.add_match(match_result);
+        }
+        for (player, total) in players.iter_mut().zip(&result.totals) {
+            player.add_score(*total);
         }
+        result
     }
 
-    pub fn play_games(&self, players: &mut [Player]) {
-        for i in 0..players.len() {
-            let (left, right) = players.split_at_mut(i + 1);
-            for j in 0..right.len() {
-                player::play_games(&mut left[i], &mut right[j], &self.payoff, self.iterations);
+    // Every pairing of every repetition, in a fixed order. A pair of equal
+    // indices stands for a match against the player's twin.
+    pub(crate) fn schedule(&self, players_num: usize) -> Vec<(u32, (usize, usize))> {
+        let mut schedule = Vec::new();
+        for repetition in 0..self.repetitions {
+            for i in 0..players_num {
+                for j in i + 1..players_num {
+                    schedule.push((repetition, (i, j)));
+                }
+                if self.play_twin {
+                    schedule.push((repetition, (i, i)));
+                }
             }
-            if self.play_twin {
-                let mut twin = left[i].twin();
-                player::play_games(&mut left[i], &mut twin, &self.payoff, self.iterations)
+        }
+        schedule
+    }
+
+    pub(crate) fn play_match(
+        &self,
+        players: &[Player],
+        index: u64,
+        repetition: u32,
+        (i, j): (usize, usize),
+    ) -> MatchResult {
+        if let Some(seed) = self.seed {
+            rng::seed(rng::derive_seed(seed, index));
+        }
+        let turns = self.match_length.sample();
+        let opponent_name = if i == j {
+            players[i].twin().name
+        } else {
+            players[j].name.clone()
+        };
+        let guards = (
+            fault::Guarded::new(players[i].strategy(), self.move_budget, self.match_budget),
+            fault::Guarded::new(players[j].strategy(), self.move_budget, self.match_budget),
+        );
+        let mut result = {
+            let first = Player::new(&players[i].name, &guards.0);
+            let opponent = Player::new(&opponent_name, &guards.1);
+            self.play_guarded(&first, &opponent, repetition, (i, j), turns)
+        };
+        let faults = [(i, j, guards.0.fault()), (j, i, guards.1.fault())];
+        result.incidents = faults
+            .into_iter()
+            .filter_map(|(player, opponent, fault)| {
+                fault.map(|(turn, fault)| Incident {
+                    player,
+                    opponent,
+                    repetition,
+                    turn,
+                    fault,
+                })
+            })
+            .collect();
+        if !result.incidents.is_empty() {
+            let reward = self
+                .payoff
+                .compute_payoff(Move::Collaborate, Move::Collaborate)
+                .0;
+            let faulted = (
+                result.incidents.iter().any(|incident| incident.player == i),
+                result.incidents.iter().any(|incident| incident.player == j),
+            );
+            result.scores = (
+                if faulted.0 {
+                    0
+                } else {
+                    u64::from(reward) * result.turns
+                },
+                if faulted.1 {
+                    0
+                } else {
+                    u64::from(reward) * result.turns
+                },
+            );
+        }
+        result
+    }
+
+    fn play_guarded(
+        &self,
+        first: &Player,
+        opponent: &Player,
+        repetition: u32,
+        (i, j): (usize, usize),
+        turns: u32,
+    ) -> MatchResult {
+        let simultaneous = self.timing == Timing::Simultaneous;
+        // Budgets are enforced on the moves actually played, so a budgeted
+        // match is played out instead of extrapolated from its cycle.
+        let budgeted = self.move_budget.is_some() || self.match_budget.is_some();
+        if self.match_mode == MatchMode::Extrapolate
+            && simultaneous
+            && self.noise == 0.0
+            && !self.record_moves
+            && !budgeted
+        {
+            let summary = cycle::summarize_match(
+                first.strategy(),
+                opponent.strategy(),
+                &self.payoff,
+                turns.into(),
+                MatchMode::Extrapolate,
+            );
+            return MatchResult::from_summary(repetition, (i, j), &summary);
+        }
+        let outcome = if simultaneous {
+            player::play_match(first, opponent, &self.payoff, turns, self.noise)
+        } else {
+            player::play_alternating_repetition(
+                first,
+                opponent,
+                &self.payoff,
+                turns,
+                self.noise,
+                repetition,
+            )
+        };
+        let mut result =
+            MatchResult::from_history(repetition, (i, j), outcome.scores, &outcome.history);
+        if self.record_moves {
+            result.rounds = Some(outcome.rounds);
+        }
+        result
+    }
+
+    // Whether every match between the same deterministic strategies ends the
+    // same way and counts the same, as there is neither noise, a random match
+    // length, a time budget nor disqualification, and no first mover that
+    // changes with the order of the players and the repetition.
+    pub(crate) fn is_deterministic(&self) -> bool {
+        self.timing == Timing::Simultaneous
+            && self.noise == 0.0
+            && matches!(self.match_length, MatchLength::Fixed(_))
+            && self.move_budget.is_none()
+            && self.match_budget.is_none()
+            && self.fault_policy == FaultPolicy::Forfeit
+    }
+
+    // Every setting that decides the outcome of the tournament but its seed,
+    // to tell whether saved progress was made by the same tournament.
+    pub(crate) fn configuration(&self) -> String {
+        format!(
+            "{:?}",
+            (
+                &self.payoff,
+                self.match_length,
+                self.play_twin,
+                self.noise,
+                self.repetitions,
+                self.scoring,
+                self.tiebreak,
+                self.timing,
+                self.move_budget,
+                self.match_budget,
+                self.fault_policy,
+                self.complexity_cost,
+            )
+        )
+    }
+
+    pub fn payoff(&self) -> &Payoff {
+        &self.payoff
+    }
+
+    pub fn noise(&self) -> f64 {
+        self.noise
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    pub(crate) fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub(crate) fn repetitions(&self) -> u32 {
+        self.repetitions
+    }
+
+    pub(crate) fn play_twin(&self) -> bool {
+        self.play_twin
+    }
+
+    pub(crate) fn complexity_cost(&self) -> &ComplexityCost {
+        &self.complexity_cost
+    }
+
+    // Scores of the players in a result of this tournament, according to its
+    // scoring mode, after complexity costs.
+    pub fn scores(&self, result: &TournamentResult) -> Vec<f64> {
+        self.scores_from_tallies(&result.totals, &result.turns, &result.costs(), || {
+            let mut points = vec![0; result.players.len()];
+            for result in result.matches.iter().filter(|m| !m.is_self_play()) {
+                let (first, second) = result.players;
+                let (first_points, second_points) = match_points(result.scores);
+                points[first] += first_points;
+                points[second] += second_points;
+            }
+            points
+        })
+    }
+
+    // Scores from the total payoff, turns, complexity costs and league points
+    // of every player. The points are only computed when the scoring mode
+    // needs them, and aren't charged for complexity.
+    pub(crate) fn scores_from_tallies(
+        &self,
+        totals: &[u64],
+        turns: &[u64],
+        costs: &[f64],
+        points: impl FnOnce() -> Vec<u32>,
+    ) -> Vec<f64> {
+        let net = totals
+            .iter()
+            .zip(costs)
+            .map(|(total, cost)| *total as f64 - cost);
+        match self.scoring {
+            ScoringMode::TotalPayoff => net.collect(),
+            ScoringMode::PayoffPerTurn => net
+                .zip(turns)
+                .map(|(net, turns)| match turns {
+                    0 => 0.0,
+                    turns => net / *turns as f64,
+                })
+                .collect(),
+            ScoringMode::WinDrawLoss => points().into_iter().map(f64::from).collect(),
+        }
+    }
+
+    // Players with the best score in a result of this tournament. Ties are
+    // settled by the tiebreak, so there is a single winner unless it is
+    // `Tiebreak::Shared`. Disqualified players only win when all are.
+    pub fn winners(&self, result: &TournamentResult) -> Vec<usize> {
+        let mut scores = self.scores(result);
+        for player in &result.disqualified {
+            scores[*player] = f64::NEG_INFINITY;
+        }
+        self.winners_by_score(&scores, &result.totals)
+    }
+
+    pub(crate) fn winners_by_score(&self, scores: &[f64], totals: &[u64]) -> Vec<usize> {
+        let Some(best) = scores.iter().copied().max_by(f64::total_cmp) else {
+            return Vec::new();
+        };
+        let tied: Vec<usize> = (0..scores.len())
+            .filter(|player| scores[*player] == best)
+            .collect();
+        match self.tiebreak {
+            Tiebreak::LastPlayer => vec![tied[tied.len() - 1]],
+            Tiebreak::FirstPlayer => vec![tied[0]],
+            Tiebreak::TotalPayoff => {
+                let best_total = tied.iter().map(|player| totals[*player]).max();
+                tied.into_iter()
+                    .filter(|player| Some(totals[*player]) == best_total)
+                    .take(1)
+                    .collect()
+            }
+            Tiebreak::Random => {
+                // Every result gets its own draw, so that ties in different
+                // results of one tournament aren't all settled alike.
+                if let Some(seed) = self.seed {
+                    let stream = totals.iter().fold(TIEBREAK_STREAM, |stream, total| {
+                        rng::derive_seed(stream, *total)
+                    });
+                    rng::seed(rng::derive_seed(seed, stream));
+                }
+                vec![tied[rng::with_rng(|rng| rng.gen_range(0..tied.len()))]]
             }
+            Tiebreak::Shared => tied,
         }
     }
 }
 
+// League points of both players of a match with the given scores.
+pub(crate) fn match_points(scores: (u64, u64)) -> (u32, u32) {
+    match scores.0.cmp(&scores.1) {
+        Ordering::Greater => (WIN_POINTS, 0),
+        Ordering::Equal => (DRAW_POINTS, DRAW_POINTS),
+        Ordering::Less => (0, WIN_POINTS),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::player::GamePlay;
+    use crate::progress::CancellationToken;
+    use crate::strategy::drunk::Drunk;
+    use crate::strategy::grim_trigger::GrimTrigger;
     use crate::strategy::naive::Naive;
     use crate::strategy::nasty::Nasty;
+    use crate::strategy::random::Random;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::strategy::Strategy;
 
     #[test]
     fn all_pairs_single_player() {
@@ -64,9 +513,16 @@ mod tests {
             Player::new("naive_player", &naive_strategy),
             Player::new("nasty_player", &nasty_strategy),
         ];
-        tournament.play_games(&mut players);
+        let result = tournament.play_games(&mut players);
         assert_eq!(players[0].score(), 0);
         assert_eq!(players[1].score(), 50);
+        assert_eq!(result.totals, vec![0, 50]);
+        assert_eq!(result.score_matrix, vec![vec![0, 0], vec![50, 0]]);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].players, (0, 1));
+        assert_eq!(result.matches[0].scores, (0, 50));
+        assert_eq!(result.matches[0].cooperations, (10, 0));
+        assert_eq!(result.matches[0].defections, (0, 10));
     }
 
     #[test]
@@ -104,9 +560,431 @@ mod tests {
             Player::new("nasty_player_1", &nasty_strategy),
             Player::new("nasty_player_2", &nasty_strategy),
         ];
-        tournament.play_games(&mut players);
+        let result = tournament.play_games(&mut players);
         assert_eq!(players[0].score(), 30);
         assert_eq!(players[1].score(), 70);
         assert_eq!(players[2].score(), 70);
+        assert_eq!(result.totals, vec![30, 70, 70]);
+        assert_eq!(result.turns, vec![30, 30, 30]);
+        assert_eq!(
+            result.score_matrix,
+            vec![vec![30, 0, 0], vec![50, 10, 10], vec![50, 10, 10]]
+        );
+        assert_eq!(result.matches.len(), 6);
+        assert_eq!(
+            result.matches.iter().filter(|m| m.is_self_play()).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn repetitions_accumulate_scores() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let tournament = Tournament::builder()
+            .match_length(5)
+            .repetitions(3)
+            .build()
+            .unwrap();
+        let mut players = vec![
+            Player::new("naive_player", &naive_strategy),
+            Player::new("nasty_player", &nasty_strategy),
+        ];
+        let result = tournament.play_games(&mut players);
+        assert_eq!(players[0].score(), 0);
+        assert_eq!(players[1].score(), 75);
+        assert_eq!(result.totals, vec![0, 75]);
+        assert_eq!(result.turns, vec![15, 15]);
+        let repetitions: Vec<_> = result.matches.iter().map(|m| m.repetition).collect();
+        assert_eq!(repetitions, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn full_noise_flips_every_move() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let tournament = Tournament::builder().noise(1.0).build().unwrap();
+        let mut players = vec![
+            Player::new("naive_player", &naive_strategy),
+            Player::new("nasty_player", &nasty_strategy),
+        ];
+        tournament.play_games(&mut players);
+        assert_eq!(players[0].score(), 50);
+        assert_eq!(players[1].score(), 0);
+    }
+
+    #[test]
+    fn seeded_tournament_is_reproducible() {
+        let random_strategy = Random {};
+        let tournament = Tournament::builder()
+            .match_length_distribution(MatchLength::Geometric {
+                continuation_probability: 0.9,
+            })
+            .noise(0.1)
+            .seed(7)
+            .build()
+            .unwrap();
+        let play = || {
+            let mut players = vec![
+                Player::new("random_1", &random_strategy),
+                Player::new("random_2", &random_strategy),
+                Player::new("random_3", &random_strategy),
+            ];
+            tournament.play_games(&mut players)
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn thread_count_does_not_change_results() {
+        let strategies = crate::strategy::all_strategies();
+        let tournament = Tournament::builder()
+            .match_length_distribution(MatchLength::Uniform { min: 10, max: 50 })
+            .noise(0.05)
+            .repetitions(3)
+            .self_play(true)
+            .seed(11)
+            .build()
+            .unwrap();
+        let play_with_threads = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let mut players: Vec<_> = strategies
+                .iter()
+                .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+                .collect();
+            let result = pool.install(|| tournament.play_games(&mut players));
+            let scores: Vec<_> = players.iter().map(Player::score).collect();
+            assert_eq!(scores, result.totals);
+            result
+        };
+        let single_threaded = play_with_threads(1);
+        assert_eq!(single_threaded, play_with_threads(4));
+        assert_eq!(single_threaded, play_with_threads(7));
+    }
+
+    #[test]
+    fn uniform_match_length() {
+        let naive_strategy = Naive {};
+        let tournament = Tournament::builder()
+            .match_length_distribution(MatchLength::Uniform { min: 3, max: 4 })
+            .build()
+            .unwrap();
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("naive_2", &naive_strategy),
+        ];
+        tournament.play_games(&mut players);
+        assert!([9, 12].contains(&players[0].score()));
+    }
+
+    #[test]
+    fn per_turn_scores() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let tournament = Tournament::builder()
+            .scoring(ScoringMode::PayoffPerTurn)
+            .build()
+            .unwrap();
+        let mut players = vec![
+            Player::new("naive_player", &naive_strategy),
+            Player::new("nasty_player_1", &nasty_strategy),
+            Player::new("nasty_player_2", &nasty_strategy),
+        ];
+        let result = tournament.play_games(&mut players);
+        assert_eq!(tournament.scores(&result), vec![0.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn win_draw_loss_points() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let tournament = Tournament::builder()
+            .scoring(ScoringMode::WinDrawLoss)
+            .self_play(true)
+            .build()
+            .unwrap();
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("nasty", &nasty_strategy),
+            Player::new("naive_2", &naive_strategy),
+        ];
+        let result = tournament.play_games(&mut players);
+        assert_eq!(tournament.scores(&result), vec![1.0, 6.0, 1.0]);
+        assert_eq!(tournament.winners(&result), vec![1]);
+    }
+
+    #[test]
+    fn tiebreaks() {
+        let naive_strategy = Naive {};
+        let nasty_strategy = Nasty {};
+        let tournament_with = |tiebreak| {
+            Tournament::builder()
+                .scoring(ScoringMode::WinDrawLoss)
+                .tiebreak(tiebreak)
+                .seed(3)
+                .build()
+                .unwrap()
+        };
+        let mut players = vec![
+            Player::new("naive", &naive_strategy),
+            Player::new("nasty_1", &nasty_strategy),
+            Player::new("nasty_2", &nasty_strategy),
+        ];
+        // Both Nasty players get 3 + 1 points and the same total payoff.
+        let result = tournament_with(Tiebreak::Shared).play_games(&mut players);
+        assert_eq!(result.totals, vec![0, 60, 60]);
+        assert_eq!(
+            tournament_with(Tiebreak::Shared).winners(&result),
+            vec![1, 2]
+        );
+        assert_eq!(
+            tournament_with(Tiebreak::LastPlayer).winners(&result),
+            vec![2]
+        );
+        assert_eq!(
+            tournament_with(Tiebreak::FirstPlayer).winners(&result),
+            vec![1]
+        );
+        assert_eq!(
+            tournament_with(Tiebreak::TotalPayoff).winners(&result),
+            vec![1]
+        );
+        let random = tournament_with(Tiebreak::Random).winners(&result);
+        assert!(random == vec![1] || random == vec![2]);
+        assert_eq!(tournament_with(Tiebreak::Random).winners(&result), random);
+    }
+
+    #[test]
+    fn total_payoff_tiebreak() {
+        let mut result = TournamentResult::new(&[]);
+        result.players = vec!["a".to_string(), "b".to_string()];
+        result.totals = vec![10, 20];
+        result.matches = vec![MatchResult::from_history(0, (0, 1), (10, 10), &[])];
+        let tournament = Tournament::builder()
+            .scoring(ScoringMode::WinDrawLoss)
+            .build()
+            .unwrap();
+        assert_eq!(tournament.scores(&result), vec![1.0, 1.0]);
+        assert_eq!(tournament.winners(&result), vec![1]);
+    }
+
+    #[test]
+    fn extrapolated_matches_equal_simulated_ones() {
+        let strategies = crate::strategy::all_strategies();
+        let tournament_with = |match_mode| {
+            Tournament::builder()
+                .match_length(2_001)
+                .self_play(true)
+                .match_mode(match_mode)
+                .seed(13)
+                .build()
+                .unwrap()
+        };
+        let play = |match_mode| {
+            let mut players: Vec<_> = strategies
+                .iter()
+                .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+                .collect();
+            tournament_with(match_mode).play_games(&mut players)
+        };
+        assert_eq!(play(MatchMode::Simulate), play(MatchMode::Extrapolate));
+    }
+
+    #[test]
+    fn billion_turn_matches() {
+        let naive_strategy = Naive {};
+        let nasty_strategy = Nasty {};
+        let mut players = vec![
+            Player::new("naive", &naive_strategy),
+            Player::new("nasty", &nasty_strategy),
+        ];
+        let tournament = Tournament::builder()
+            .match_length(1_000_000_000)
+            .match_mode(MatchMode::Extrapolate)
+            .build()
+            .unwrap();
+        let result = tournament.play_games(&mut players);
+        assert_eq!(result.totals, vec![0, 5_000_000_000]);
+        assert_eq!(result.turns, vec![1_000_000_000; 2]);
+        assert_eq!(players[1].score(), 5_000_000_000);
+    }
+
+    #[test]
+    fn alternating_timing() {
+        let drunk_strategy = Drunk {};
+        let tit_for_tat_strategy = TitForTat {};
+        let tournament_with = |timing| {
+            let tournament = Tournament::builder()
+                .match_length(4)
+                .timing(timing)
+                .build()
+                .unwrap();
+            let mut players = vec![
+                Player::new("drunk", &drunk_strategy),
+                Player::new("tit_for_tat", &tit_for_tat_strategy),
+            ];
+            tournament.play_games(&mut players);
+            (players[0].score(), players[1].score())
+        };
+        assert_eq!(tournament_with(Timing::Simultaneous), (13, 8));
+        assert_eq!(tournament_with(Timing::Alternating), (8, 8));
+    }
+
+    #[test]
+    fn complexity_costs_decide_winners() {
+        let naive_strategy = Naive {};
+        let grim_trigger_strategy = GrimTrigger {};
+        let mut players = vec![
+            Player::new("naive", &naive_strategy),
+            Player::new("grim_trigger", &grim_trigger_strategy),
+        ];
+        let free = Tournament::all_pairs();
+        let result = free.play_games(&mut players);
+        assert_eq!(free.winners(&result), vec![1]);
+        let charged = Tournament::builder()
+            .complexity_cost(ComplexityCost {
+                per_memory_turn: 0.0,
+                per_state: 0.1,
+                per_match: 0.0,
+            })
+            .scoring(ScoringMode::PayoffPerTurn)
+            .build()
+            .unwrap();
+        let result = charged.play_games(&mut players);
+        assert_eq!(result.totals, vec![30, 30]);
+        assert_eq!(charged.scores(&result), vec![3.0, 2.8]);
+        assert_eq!(charged.winners(&result), vec![0]);
+    }
+
+    struct Panicking;
+
+    impl Strategy for Panicking {
+        fn play(&self, _past_games: &[GamePlay]) -> Move {
+            panic!("out of ideas");
+        }
+    }
+
+    fn play_with_faulty_player(fault_policy: FaultPolicy) -> TournamentResult {
+        let naive_strategy = Naive {};
+        let panicking_strategy = Panicking;
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("panicking", &panicking_strategy),
+            Player::new("naive_2", &naive_strategy),
+        ];
+        Tournament::builder()
+            .fault_policy(fault_policy)
+            .build()
+            .unwrap()
+            .play_games(&mut players)
+    }
+
+    #[test]
+    fn faulty_player_forfeits() {
+        let result = play_with_faulty_player(FaultPolicy::Forfeit);
+        assert_eq!(result.totals, vec![60, 0, 60]);
+        assert!(result.disqualified.is_empty());
+        let incidents: Vec<_> = result.incidents().collect();
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[0].player, 1);
+        assert_eq!(incidents[0].opponent, 0);
+        assert_eq!(incidents[0].turn, 0);
+        assert_eq!(
+            incidents[0].fault,
+            Fault::Panic {
+                message: "out of ideas".to_string()
+            }
+        );
+    }
+
+    struct LatePanicking;
+
+    impl Strategy for LatePanicking {
+        fn play(&self, past_games: &[GamePlay]) -> Move {
+            if past_games.len() == 50 {
+                panic!("late");
+            }
+            Move::Collaborate
+        }
+    }
+
+    #[test]
+    fn budgeted_matches_are_not_extrapolated() {
+        let naive_strategy = Naive {};
+        let late_strategy = LatePanicking;
+        let mut players = vec![
+            Player::new("naive", &naive_strategy),
+            Player::new("late", &late_strategy),
+        ];
+        let result = Tournament::builder()
+            .match_length(100)
+            .match_mode(MatchMode::Extrapolate)
+            .move_budget(Duration::from_secs(10))
+            .build()
+            .unwrap()
+            .play_games(&mut players);
+        let incidents: Vec<_> = result.incidents().collect();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].turn, 50);
+        assert_eq!(result.totals, vec![300, 0]);
+    }
+
+    #[test]
+    fn faulty_player_is_disqualified() {
+        let result = play_with_faulty_player(FaultPolicy::Disqualify);
+        assert_eq!(result.disqualified, vec![1]);
+        assert_eq!(result.totals, vec![30, 0, 30]);
+        assert_eq!(result.incidents().count(), 2);
+        let ranking = result.ranking();
+        assert_eq!(ranking.players[2].name, "panicking");
+        assert!(ranking.players[2].disqualified);
+        let tournament = Tournament::builder()
+            .fault_policy(FaultPolicy::Disqualify)
+            .tiebreak(Tiebreak::Shared)
+            .build()
+            .unwrap();
+        assert_eq!(tournament.winners(&result), vec![0, 2]);
+    }
+
+    #[test]
+    fn cancelled_tournament_keeps_played_matches() {
+        let naive_strategy = Naive {};
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("naive_2", &naive_strategy),
+        ];
+        let token = CancellationToken::default();
+        token.cancel();
+        let monitor = Monitor {
+            progress: None,
+            cancellation: Some(token),
+        };
+        let result = Tournament::all_pairs().play_games_monitored(&mut players, &monitor);
+        assert!(result.cancelled);
+        assert!(result.matches.is_empty());
+        assert_eq!(players[0].score(), 0);
+    }
+
+    #[test]
+    fn reports_every_match() {
+        let naive_strategy = Naive {};
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("naive_2", &naive_strategy),
+            Player::new("naive_3", &naive_strategy),
+        ];
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let monitor = Monitor {
+            progress: Some(sender),
+            cancellation: None,
+        };
+        let result = Tournament::all_pairs().play_games_monitored(&mut players, &monitor);
+        drop(monitor);
+        assert!(!result.cancelled);
+        let last = receiver.iter().last().unwrap();
+        assert_eq!((last.completed, last.total), (3, 3));
     }
 }
@@ -0,0 +1,368 @@
+use super::{
+    ComplexityCost, FaultPolicy, MatchLength, ScoringMode, Tiebreak, Tournament, DEFAULT_ITERATIONS,
+};
+use crate::cycle::MatchMode;
+use crate::game::{Payoff, Timing};
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TournamentError {
+    ZeroMatchLength,
+    EmptyMatchLengthRange { min: u32, max: u32 },
+    InvalidContinuationProbability(f64),
+    InvalidNoise(f64),
+    ZeroRepetitions,
+    // Some cost is negative or not a number.
+    InvalidComplexityCost(ComplexityCost),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentError::ZeroMatchLength => write!(f, "matches must last at least one turn"),
+            TournamentError::EmptyMatchLengthRange { min, max } => {
+                write!(f, "match length range {}..={} is empty", min, max)
+            }
+            TournamentError::InvalidContinuationProbability(probability) => write!(
+                f,
+                "continuation probability {} is not in [0, 1)",
+                probability
+            ),
+            TournamentError::InvalidNoise(noise) => {
+                write!(f, "noise {} is not in [0, 1]", noise)
+            }
+            TournamentError::ZeroRepetitions => {
+                write!(f, "tournament must be played at least once")
+            }
+            TournamentError::InvalidComplexityCost(cost) => write!(
+                f,
+                "complexity costs must be non-negative numbers: {} per memory turn, {} per state, {} per match",
+                cost.per_memory_turn, cost.per_state, cost.per_match
+            ),
+        }
+    }
+}
+
+impl Error for TournamentError {}
+
+#[derive(Debug, Clone)]
+pub struct TournamentBuilder {
+    payoff: Payoff,
+    match_length: MatchLength,
+    play_twin: bool,
+    noise: f64,
+    repetitions: u32,
+    seed: Option<u64>,
+    scoring: ScoringMode,
+    tiebreak: Tiebreak,
+    match_mode: MatchMode,
+    record_moves: bool,
+    timing: Timing,
+    move_budget: Option<Duration>,
+    match_budget: Option<Duration>,
+    fault_policy: FaultPolicy,
+    complexity_cost: ComplexityCost,
+}
+
+impl Default for TournamentBuilder {
+    fn default() -> Self {
+        Self {
+            payoff: Payoff::default(),
+            match_length: MatchLength::Fixed(DEFAULT_ITERATIONS),
+            play_twin: false,
+            noise: 0.0,
+            repetitions: 1,
+            seed: None,
+            scoring: ScoringMode::TotalPayoff,
+            tiebreak: Tiebreak::LastPlayer,
+            match_mode: MatchMode::Simulate,
+            record_moves: false,
+            timing: Timing::Simultaneous,
+            move_budget: None,
+            match_budget: None,
+            fault_policy: FaultPolicy::Forfeit,
+            complexity_cost: ComplexityCost::default(),
+        }
+    }
+}
+
+impl TournamentBuilder {
+    pub fn payoff(mut self, payoff: Payoff) -> Self {
+        self.payoff = payoff;
+        self
+    }
+
+    pub fn match_length(self, turns: u32) -> Self {
+        self.match_length_distribution(MatchLength::Fixed(turns))
+    }
+
+    pub fn match_length_distribution(mut self, match_length: MatchLength) -> Self {
+        self.match_length = match_length;
+        self
+    }
+
+    // Whether every player also plays a match against its own twin.
+    pub fn self_play(mut self, play_twin: bool) -> Self {
+        self.play_twin = play_twin;
+        self
+    }
+
+    // Probability of every move being flipped after the player chose it.
+    pub fn noise(mut self, noise: f64) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    // Number of times the whole round robin is played. Scores accumulate over
+    // all repetitions, histories don't.
+    pub fn repetitions(mut self, repetitions: u32) -> Self {
+        self.repetitions = repetitions;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn scoring(mut self, scoring: ScoringMode) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    pub fn tiebreak(mut self, tiebreak: Tiebreak) -> Self {
+        self.tiebreak = tiebreak;
+        self
+    }
+
+    // Extrapolation only applies to noiseless matches, noisy ones are always
+    // simulated.
+    pub fn match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.match_mode = match_mode;
+        self
+    }
+
+    // Keeps every round of every match in the result, for transcripts.
+    // Recorded matches are never extrapolated.
+    pub fn record_moves(mut self, record_moves: bool) -> Self {
+        self.record_moves = record_moves;
+        self
+    }
+
+    // In an alternating tournament the first player of every match moves
+    // first in every round of even repetitions, and its opponent in odd
+    // ones.
+    pub fn timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    // Longest time a strategy may take for a single move.
+    pub fn move_budget(mut self, budget: Duration) -> Self {
+        self.move_budget = Some(budget);
+        self
+    }
+
+    // Longest time a strategy may take for all of its moves in a match.
+    pub fn match_budget(mut self, budget: Duration) -> Self {
+        self.match_budget = Some(budget);
+        self
+    }
+
+    // What happens to a player whose strategy panics or exceeds a budget.
+    pub fn fault_policy(mut self, fault_policy: FaultPolicy) -> Self {
+        self.fault_policy = fault_policy;
+        self
+    }
+
+    // Costs taken off the payoffs for the complexity of the strategies.
+    // Win-draw-loss scoring only counts match points and ignores them.
+    pub fn complexity_cost(mut self, complexity_cost: ComplexityCost) -> Self {
+        self.complexity_cost = complexity_cost;
+        self
+    }
+
+    pub fn build(self) -> Result<Tournament, TournamentError> {
+        match self.match_length {
+            MatchLength::Fixed(0) => return Err(TournamentError::ZeroMatchLength),
+            MatchLength::Uniform { min: 0, .. } => return Err(TournamentError::ZeroMatchLength),
+            MatchLength::Uniform { min, max } if min > max => {
+                return Err(TournamentError::EmptyMatchLengthRange { min, max })
+            }
+            MatchLength::Geometric {
+                continuation_probability,
+            } if !(0.0..1.0).contains(&continuation_probability) => {
+                return Err(TournamentError::InvalidContinuationProbability(
+                    continuation_probability,
+                ))
+            }
+            _ => {}
+        }
+        if !(0.0..=1.0).contains(&self.noise) {
+            return Err(TournamentError::InvalidNoise(self.noise));
+        }
+        if self.repetitions == 0 {
+            return Err(TournamentError::ZeroRepetitions);
+        }
+        let cost = self.complexity_cost;
+        if [cost.per_memory_turn, cost.per_state, cost.per_match]
+            .iter()
+            .any(|cost| cost.is_nan() || *cost < 0.0)
+        {
+            return Err(TournamentError::InvalidComplexityCost(cost));
+        }
+        Ok(Tournament {
+            payoff: self.payoff,
+            match_length: self.match_length,
+            play_twin: self.play_twin,
+            noise: self.noise,
+            repetitions: self.repetitions,
+            seed: self.seed,
+            scoring: self.scoring,
+            tiebreak: self.tiebreak,
+            match_mode: self.match_mode,
+            record_moves: self.record_moves,
+            timing: self.timing,
+            move_budget: self.move_budget,
+            match_budget: self.match_budget,
+            fault_policy: self.fault_policy,
+            complexity_cost: self.complexity_cost,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let tournament = TournamentBuilder::default().build().unwrap();
+        assert_eq!(tournament.payoff, Payoff::default());
+        assert_eq!(
+            tournament.match_length,
+            MatchLength::Fixed(DEFAULT_ITERATIONS)
+        );
+        assert!(!tournament.play_twin);
+        assert_eq!(tournament.noise, 0.0);
+        assert_eq!(tournament.repetitions, 1);
+        assert_eq!(tournament.seed, None);
+        assert_eq!(tournament.scoring, ScoringMode::TotalPayoff);
+        assert_eq!(tournament.tiebreak, Tiebreak::LastPlayer);
+        assert_eq!(tournament.match_mode, MatchMode::Simulate);
+        assert!(!tournament.record_moves);
+        assert_eq!(tournament.timing, Timing::Simultaneous);
+        assert_eq!(tournament.move_budget, None);
+        assert_eq!(tournament.match_budget, None);
+        assert_eq!(tournament.fault_policy, FaultPolicy::Forfeit);
+        assert_eq!(tournament.complexity_cost, ComplexityCost::default());
+    }
+
+    const COST: ComplexityCost = ComplexityCost {
+        per_memory_turn: 0.1,
+        per_state: 0.05,
+        per_match: 1.0,
+    };
+
+    #[test]
+    fn full_configuration() {
+        let tournament = TournamentBuilder::default()
+            .payoff(Payoff::new(4, 3, 2, 1))
+            .match_length(200)
+            .self_play(true)
+            .noise(0.05)
+            .repetitions(5)
+            .seed(42)
+            .scoring(ScoringMode::PayoffPerTurn)
+            .tiebreak(Tiebreak::Shared)
+            .match_mode(MatchMode::Extrapolate)
+            .record_moves(true)
+            .timing(Timing::Alternating)
+            .move_budget(Duration::from_millis(10))
+            .match_budget(Duration::from_secs(1))
+            .fault_policy(FaultPolicy::Disqualify)
+            .complexity_cost(COST)
+            .build()
+            .unwrap();
+        assert_eq!(tournament.payoff, Payoff::new(4, 3, 2, 1));
+        assert_eq!(tournament.match_length, MatchLength::Fixed(200));
+        assert!(tournament.play_twin);
+        assert_eq!(tournament.noise, 0.05);
+        assert_eq!(tournament.repetitions, 5);
+        assert_eq!(tournament.seed, Some(42));
+        assert_eq!(tournament.scoring, ScoringMode::PayoffPerTurn);
+        assert_eq!(tournament.tiebreak, Tiebreak::Shared);
+        assert_eq!(tournament.match_mode, MatchMode::Extrapolate);
+        assert!(tournament.record_moves);
+        assert_eq!(tournament.timing, Timing::Alternating);
+        assert_eq!(tournament.move_budget, Some(Duration::from_millis(10)));
+        assert_eq!(tournament.match_budget, Some(Duration::from_secs(1)));
+        assert_eq!(tournament.fault_policy, FaultPolicy::Disqualify);
+        assert_eq!(tournament.complexity_cost, COST);
+    }
+
+    #[test]
+    fn invalid_match_length() {
+        assert_eq!(
+            TournamentBuilder::default().match_length(0).build().err(),
+            Some(TournamentError::ZeroMatchLength)
+        );
+        assert_eq!(
+            TournamentBuilder::default()
+                .match_length_distribution(MatchLength::Uniform { min: 5, max: 4 })
+                .build()
+                .err(),
+            Some(TournamentError::EmptyMatchLengthRange { min: 5, max: 4 })
+        );
+        assert_eq!(
+            TournamentBuilder::default()
+                .match_length_distribution(MatchLength::Geometric {
+                    continuation_probability: 1.0
+                })
+                .build()
+                .err(),
+            Some(TournamentError::InvalidContinuationProbability(1.0))
+        );
+    }
+
+    #[test]
+    fn invalid_noise() {
+        assert_eq!(
+            TournamentBuilder::default().noise(-0.1).build().err(),
+            Some(TournamentError::InvalidNoise(-0.1))
+        );
+        assert_eq!(
+            TournamentBuilder::default().noise(1.5).build().err(),
+            Some(TournamentError::InvalidNoise(1.5))
+        );
+    }
+
+    #[test]
+    fn invalid_repetitions() {
+        assert_eq!(
+            TournamentBuilder::default().repetitions(0).build().err(),
+            Some(TournamentError::ZeroRepetitions)
+        );
+        for cost in [
+            ComplexityCost {
+                per_memory_turn: -0.1,
+                ..ComplexityCost::default()
+            },
+            ComplexityCost {
+                per_state: f64::NAN,
+                ..ComplexityCost::default()
+            },
+            ComplexityCost {
+                per_match: -1.0,
+                ..ComplexityCost::default()
+            },
+        ] {
+            assert!(matches!(
+                TournamentBuilder::default().complexity_cost(cost).build(),
+                Err(TournamentError::InvalidComplexityCost(_))
+            ));
+        }
+    }
+}
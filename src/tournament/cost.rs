@@ -0,0 +1,39 @@
+use crate::strategy::Complexity;
+
+// Price of the complexity of a strategy, taken off its payoff. Costs are
+// charged to every player on every turn it plays and for every match.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ComplexityCost {
+    // Charged per turn for every past turn the strategy remembers.
+    pub per_memory_turn: f64,
+    // Charged per turn for every state of a finite state machine strategy.
+    pub per_state: f64,
+    // Charged once per match, whatever the strategy.
+    pub per_match: f64,
+}
+
+impl ComplexityCost {
+    pub fn per_turn(&self, complexity: Complexity) -> f64 {
+        match complexity {
+            Complexity::MemoryDepth(depth) => self.per_memory_turn * f64::from(depth),
+            Complexity::States(states) => self.per_state * f64::from(states),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_turn_cost() {
+        let cost = ComplexityCost {
+            per_memory_turn: 0.5,
+            per_state: 0.25,
+            per_match: 2.0,
+        };
+        assert_eq!(cost.per_turn(Complexity::MemoryDepth(0)), 0.0);
+        assert_eq!(cost.per_turn(Complexity::MemoryDepth(3)), 1.5);
+        assert_eq!(cost.per_turn(Complexity::States(2)), 0.5);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::game::Move;
+use crate::player::{GamePlay, OrderedMove};
+use crate::strategy::{Complexity, Strategy};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
+
+// What happens to a player whose strategy panics or runs out of time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultPolicy {
+    // The player loses the match: it scores nothing and its opponent scores
+    // as if both had cooperated on every turn.
+    Forfeit,
+    // The player also leaves the round robin: all of its matches are voided
+    // and it can't win. Other formats treat it as a forfeit.
+    Disqualify,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Fault {
+    Panic { message: String },
+    // A single move took longer than the move budget.
+    MoveTimeout { elapsed: Duration },
+    // The moves of the match together took longer than the match budget.
+    MatchTimeout { elapsed: Duration },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::Panic { message } => write!(f, "panicked: {}", message),
+            Fault::MoveTimeout { elapsed } => write!(f, "took {:?} for a move", elapsed),
+            Fault::MatchTimeout { elapsed } => write!(f, "took {:?} for the match", elapsed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    // Indices into `TournamentResult::players`, equal in a match against a
+    // twin.
+    pub player: usize,
+    pub opponent: usize,
+    pub repetition: u32,
+    // Turn of the match on which the fault happened, from zero.
+    pub turn: u32,
+    pub fault: Fault,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    turn: u32,
+    elapsed: Duration,
+    fault: Option<(u32, Fault)>,
+}
+
+// Plays a strategy on behalf of a player, turning its panics and overlong
+// moves into a fault. A running move can't be interrupted, so a slow move is
+// only caught once it returns. After a fault the strategy isn't asked again
+// and the guard cooperates.
+pub(super) struct Guarded<'a> {
+    strategy: &'a dyn Strategy,
+    move_budget: Option<Duration>,
+    match_budget: Option<Duration>,
+    state: Mutex<GuardState>,
+}
+
+impl<'a> Guarded<'a> {
+    pub(super) fn new(
+        strategy: &'a dyn Strategy,
+        move_budget: Option<Duration>,
+        match_budget: Option<Duration>,
+    ) -> Self {
+        Self {
+            strategy,
+            move_budget,
+            match_budget,
+            state: Mutex::new(GuardState::default()),
+        }
+    }
+
+    // The turn and kind of the fault, if there was one.
+    pub(super) fn fault(self) -> Option<(u32, Fault)> {
+        self.state.into_inner().expect("guard state").fault
+    }
+
+    fn guard(&self, play: impl FnOnce() -> Move) -> Move {
+        let mut state = self.state.lock().expect("guard state");
+        if state.fault.is_some() {
+            return Move::Collaborate;
+        }
+        let start = Instant::now();
+        let played = quietly(|| panic::catch_unwind(AssertUnwindSafe(play)));
+        let elapsed = start.elapsed();
+        state.elapsed += elapsed;
+        let turn = state.turn;
+        state.turn += 1;
+        let fault = match played {
+            Err(payload) => Fault::Panic {
+                message: panic_message(payload),
+            },
+            Ok(_) if self.move_budget.is_some_and(|budget| elapsed > budget) => {
+                Fault::MoveTimeout { elapsed }
+            }
+            Ok(_)
+                if self
+                    .match_budget
+                    .is_some_and(|budget| state.elapsed > budget) =>
+            {
+                Fault::MatchTimeout {
+                    elapsed: state.elapsed,
+                }
+            }
+            Ok(played) => return played,
+        };
+        state.fault = Some((turn, fault));
+        Move::Collaborate
+    }
+}
+
+impl Strategy for Guarded<'_> {
+    fn play(&self, past_games: &[GamePlay]) -> Move {
+        self.guard(|| self.strategy.play(past_games))
+    }
+
+    fn name(&self) -> String {
+        self.strategy.name()
+    }
+
+    fn play_alternating(&self, replies: &[GamePlay], past_moves: &[OrderedMove]) -> Move {
+        self.guard(|| self.strategy.play_alternating(replies, past_moves))
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.strategy.is_deterministic()
+    }
+
+    // Once faulted, the guard always cooperates whatever the history.
+    fn state(&self, past_games: &[GamePlay]) -> Option<u64> {
+        let faulted = self.state.lock().expect("guard state").fault.is_some();
+        if faulted {
+            Some(u64::MAX)
+        } else {
+            self.strategy.state(past_games)
+        }
+    }
+
+    fn complexity(&self) -> Complexity {
+        self.strategy.complexity()
+    }
+}
+
+thread_local! {
+    static GUARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Runs `play` without the panic hook reporting its panics, which become
+// faults instead. Panics anywhere else are still reported by the hook that
+// was installed before.
+fn quietly<T>(play: impl FnOnce() -> T) -> T {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let report = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !GUARDING.with(Cell::get) {
+                report(info);
+            }
+        }));
+    });
+    let outer = GUARDING.with(|guarding| guarding.replace(true));
+    let played = play();
+    GUARDING.with(|guarding| guarding.set(outer));
+    played
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+
+    struct Panicking;
+
+    impl Strategy for Panicking {
+        fn play(&self, past_games: &[GamePlay]) -> Move {
+            if past_games.len() == 2 {
+                panic!("third move");
+            }
+            Move::Defect
+        }
+    }
+
+    struct Slow;
+
+    impl Strategy for Slow {
+        fn play(&self, _past_games: &[GamePlay]) -> Move {
+            std::thread::sleep(Duration::from_millis(10));
+            Move::Defect
+        }
+    }
+
+    #[test]
+    fn catches_panics() {
+        let strategy = Panicking;
+        let guarded = Guarded::new(&strategy, None, None);
+        assert_eq!(guarded.play(&[]), Move::Defect);
+        let games = [GamePlay {
+            my_move: Move::Defect,
+            their_move: Move::Collaborate,
+        }; 2];
+        assert_eq!(guarded.play(&games), Move::Collaborate);
+        assert_eq!(guarded.play(&games[..1]), Move::Collaborate);
+        assert_eq!(
+            guarded.fault(),
+            Some((
+                1,
+                Fault::Panic {
+                    message: "third move".to_string()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn enforces_budgets() {
+        let strategy = Slow;
+        let guarded = Guarded::new(&strategy, Some(Duration::from_millis(1)), None);
+        guarded.play(&[]);
+        assert!(matches!(
+            guarded.fault(),
+            Some((0, Fault::MoveTimeout { .. }))
+        ));
+        let guarded = Guarded::new(&strategy, None, Some(Duration::from_millis(25)));
+        for _ in 0..2 {
+            assert_eq!(guarded.play(&[]), Move::Defect);
+        }
+        assert_eq!(guarded.play(&[]), Move::Collaborate);
+        assert!(matches!(
+            guarded.fault(),
+            Some((2, Fault::MatchTimeout { .. }))
+        ));
+    }
+
+    #[test]
+    fn well_behaved_strategies_are_untouched() {
+        let strategy = Naive {};
+        let guarded = Guarded::new(&strategy, Some(Duration::from_secs(1)), None);
+        assert_eq!(guarded.play(&[]), Move::Collaborate);
+        assert_eq!(guarded.name(), "Naive");
+        assert_eq!(guarded.fault(), None);
+    }
+}
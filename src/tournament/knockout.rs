@@ -0,0 +1,521 @@
+use super::{MatchResult, Tournament, TournamentResult};
+use crate::player::Player;
+use crate::rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Elimination {
+    Single,
+    // Players are out after their second loss. The winners bracket champion
+    // has to be beaten twice in the grand final.
+    Double,
+}
+
+// How a match in which both players scored the same is decided.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DrawTiebreak {
+    HigherSeed,
+    // Replays the match up to `max_replays` times, then falls back to the
+    // higher seed.
+    Replay { max_replays: u32 },
+    CoinFlip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Section {
+    Winners,
+    Losers,
+    GrandFinal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BracketMatch {
+    pub section: Section,
+    pub round: u32,
+    pub players: (usize, usize),
+    // The match and every replay it needed.
+    pub matches: Vec<MatchResult>,
+    pub winner: usize,
+}
+
+impl BracketMatch {
+    pub fn loser(&self) -> usize {
+        if self.winner == self.players.0 {
+            self.players.1
+        } else {
+            self.players.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bracket {
+    pub players: Vec<String>,
+    // `seeds[i]` is the seed of player `i`, 1 being the top seed.
+    pub seeds: Vec<usize>,
+    pub matches: Vec<BracketMatch>,
+    // Players that advanced without playing, by section and round.
+    pub byes: Vec<(Section, u32, usize)>,
+    pub champion: usize,
+}
+
+// Player indices ordered from best to worst total score in a round robin,
+// ready to be used as knockout seeding.
+pub fn seeding(result: &TournamentResult) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..result.players.len()).collect();
+    order.sort_by(|a, b| result.totals[*b].cmp(&result.totals[*a]).then(a.cmp(b)));
+    order
+}
+
+// Bracket slots for `size` players, a power of two, so that the top seeds can
+// only meet in the last rounds: 1 v 8, 4 v 5, 2 v 7, 3 v 6 for eight players.
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let next_size = order.len() * 2;
+        order = order
+            .iter()
+            .flat_map(|seed| [*seed, next_size + 1 - seed])
+            .collect();
+    }
+    order
+}
+
+struct BracketBuilder<'a> {
+    tournament: &'a Tournament,
+    players: &'a [Player<'a>],
+    seeds: Vec<usize>,
+    tiebreak: DrawTiebreak,
+    pairings_played: u64,
+    matches: Vec<BracketMatch>,
+    byes: Vec<(Section, u32, usize)>,
+}
+
+impl BracketBuilder<'_> {
+    // Plays every pairing of a round in parallel and returns the winners.
+    fn play_round(
+        &mut self,
+        section: Section,
+        round: u32,
+        pairs: Vec<(usize, usize)>,
+    ) -> Vec<usize> {
+        let first_pairing = self.pairings_played;
+        self.pairings_played += pairs.len() as u64;
+        let matches: Vec<_> = pairs
+            .into_par_iter()
+            .enumerate()
+            .map(|(k, pair)| self.play_pairing(section, round, first_pairing + k as u64, pair))
+            .collect();
+        let winners = matches
+            .iter()
+            .map(|bracket_match| bracket_match.winner)
+            .collect();
+        self.matches.extend(matches);
+        winners
+    }
+
+    fn play_pairing(
+        &self,
+        section: Section,
+        round: u32,
+        pairing: u64,
+        players: (usize, usize),
+    ) -> BracketMatch {
+        let higher_seed = if self.seeds[players.0] < self.seeds[players.1] {
+            players.0
+        } else {
+            players.1
+        };
+        let mut matches = Vec::new();
+        let winner = loop {
+            let index = (pairing << 16) | matches.len() as u64;
+            let result = self
+                .tournament
+                .play_match(self.players, index, round, players);
+            let replays = matches.len() as u32;
+            let scores = result.scores;
+            matches.push(result);
+            match scores.0.cmp(&scores.1) {
+                Ordering::Greater => break players.0,
+                Ordering::Less => break players.1,
+                Ordering::Equal => match self.tiebreak {
+                    DrawTiebreak::HigherSeed => break higher_seed,
+                    DrawTiebreak::Replay { max_replays } if replays >= max_replays => {
+                        break higher_seed
+                    }
+                    DrawTiebreak::Replay { .. } => continue,
+                    // The match just seeded this thread's generator, so the
+                    // flip is reproducible in a seeded tournament.
+                    DrawTiebreak::CoinFlip => {
+                        break if rng::random_bool(0.5) {
+                            players.0
+                        } else {
+                            players.1
+                        }
+                    }
+                },
+            }
+        };
+        BracketMatch {
+            section,
+            round,
+            players,
+            matches,
+            winner,
+        }
+    }
+
+    fn play_winners_round(
+        &mut self,
+        round: u32,
+        pairs: Vec<(usize, usize)>,
+        dropped: &mut Vec<usize>,
+    ) -> Vec<usize> {
+        let played_before = self.matches.len();
+        let winners = self.play_round(Section::Winners, round, pairs);
+        dropped.extend(
+            self.matches[played_before..]
+                .iter()
+                .map(BracketMatch::loser),
+        );
+        winners
+    }
+
+    // Pairs the best seed with the worst one, the second best with the second
+    // worst and so on. With an odd number of players the best seed gets a bye.
+    fn pair_by_seed(
+        &mut self,
+        section: Section,
+        round: u32,
+        mut players: Vec<usize>,
+    ) -> (Vec<(usize, usize)>, Vec<usize>) {
+        players.sort_by_key(|player| self.seeds[*player]);
+        let mut advancing = Vec::new();
+        if players.len() % 2 == 1 {
+            let bye = players.remove(0);
+            self.byes.push((section, round, bye));
+            advancing.push(bye);
+        }
+        let half = players.len() / 2;
+        let pairs = (0..half)
+            .map(|k| (players[k], players[players.len() - 1 - k]))
+            .collect();
+        (pairs, advancing)
+    }
+}
+
+impl Tournament {
+    // Plays an elimination bracket in which every pairing is decided by one
+    // match with the tournament's match settings. `seeding` lists player
+    // indices from the top seed down, e.g. from `seeding()` of a round robin.
+    pub fn play_knockout(
+        &self,
+        players: &[Player],
+        seeding: &[usize],
+        elimination: Elimination,
+        tiebreak: DrawTiebreak,
+    ) -> Bracket {
+        assert!(!players.is_empty(), "at least one player");
+        assert_eq!(seeding.len(), players.len(), "every player needs a seed");
+        let mut seeds = vec![0; players.len()];
+        for (position, player) in seeding.iter().enumerate() {
+            assert!(
+                *player < players.len() && seeds[*player] == 0,
+                "the seeding must list every player once"
+            );
+            seeds[*player] = position + 1;
+        }
+        let mut builder = BracketBuilder {
+            tournament: self,
+            players,
+            seeds,
+            tiebreak,
+            pairings_played: 0,
+            matches: Vec::new(),
+            byes: Vec::new(),
+        };
+
+        // The first winners round follows the seeded bracket, byes fill the
+        // slots of missing seeds.
+        let size = players.len().next_power_of_two().max(2);
+        let slots: Vec<Option<usize>> = bracket_order(size)
+            .into_iter()
+            .map(|seed| seeding.get(seed - 1).copied())
+            .collect();
+        let mut round = 1;
+        let pairs: Vec<_> = slots
+            .chunks(2)
+            .filter_map(|slot| Some((slot[0]?, slot[1]?)))
+            .collect();
+        let mut dropped = Vec::new();
+        let mut round_winners = builder
+            .play_winners_round(round, pairs, &mut dropped)
+            .into_iter();
+        let mut winners: Vec<usize> = slots
+            .chunks(2)
+            .map(|slot| match (slot[0], slot[1]) {
+                (Some(_), Some(_)) => round_winners.next().expect("a winner per pair"),
+                (Some(player), None) | (None, Some(player)) => {
+                    builder.byes.push((Section::Winners, round, player));
+                    player
+                }
+                (None, None) => unreachable!("at most half of the slots are byes"),
+            })
+            .collect();
+
+        // Every winners round is followed by a losers round that takes in the
+        // players who just dropped out of the winners bracket.
+        let mut losers_bracket: Vec<usize> = Vec::new();
+        loop {
+            if elimination == Elimination::Double {
+                losers_bracket.append(&mut dropped);
+                if losers_bracket.len() > 1 {
+                    let (pairs, mut advancing) =
+                        builder.pair_by_seed(Section::Losers, round, losers_bracket);
+                    advancing.extend(builder.play_round(Section::Losers, round, pairs));
+                    losers_bracket = advancing;
+                }
+            }
+            if winners.len() > 1 {
+                round += 1;
+                let pairs = winners.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+                dropped.clear();
+                winners = builder.play_winners_round(round, pairs, &mut dropped);
+            } else if losers_bracket.len() > 1 {
+                round += 1;
+            } else {
+                break;
+            }
+        }
+
+        let mut champion = winners[0];
+        if let Some(challenger) = losers_bracket.first().copied() {
+            round += 1;
+            let pair = vec![(champion, challenger)];
+            champion = builder.play_round(Section::GrandFinal, round, pair.clone())[0];
+            // Beating the winners bracket champion once only evens the losses.
+            if champion == challenger {
+                round += 1;
+                champion = builder.play_round(Section::GrandFinal, round, pair)[0];
+            }
+        }
+        Bracket {
+            players: players.iter().map(|player| player.name.clone()).collect(),
+            seeds: builder.seeds,
+            matches: builder.matches,
+            byes: builder.byes,
+            champion,
+        }
+    }
+}
+
+impl fmt::Display for Bracket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |player: usize| format!("({}) {}", self.seeds[player], self.players[player]);
+        let mut current = None;
+        for bracket_match in &self.matches {
+            let heading = (bracket_match.section, bracket_match.round);
+            if current != Some(heading) {
+                current = Some(heading);
+                writeln!(
+                    f,
+                    "{:?} round {}",
+                    bracket_match.section, bracket_match.round
+                )?;
+                for (section, round, player) in &self.byes {
+                    if (*section, *round) == heading {
+                        writeln!(f, "  {} bye", name(*player))?;
+                    }
+                }
+            }
+            let scores: Vec<_> = bracket_match
+                .matches
+                .iter()
+                .map(|result| format!("{}-{}", result.scores.0, result.scores.1))
+                .collect();
+            writeln!(
+                f,
+                "  {} vs {}: {} -> {}",
+                name(bracket_match.players.0),
+                name(bracket_match.players.1),
+                scores.join(", "),
+                self.players[bracket_match.winner]
+            )?;
+        }
+        write!(f, "Champion: {}", name(self.champion))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::grim_trigger::GrimTrigger;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::strategy::Strategy;
+
+    fn players(strategies: &[Box<dyn Strategy>]) -> Vec<Player<'_>> {
+        strategies
+            .iter()
+            .enumerate()
+            .map(|(i, strategy)| Player::new(&format!("player_{}", i), strategy.as_ref()))
+            .collect()
+    }
+
+    fn field() -> Vec<Box<dyn Strategy>> {
+        vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+            Box::new(GrimTrigger {}),
+            Box::new(Naive {}),
+        ]
+    }
+
+    #[test]
+    fn bracket_order_keeps_top_seeds_apart() {
+        assert_eq!(bracket_order(1), vec![1]);
+        assert_eq!(bracket_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(bracket_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn seeding_from_round_robin() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let mut players = players(&strategies);
+        let result = Tournament::all_pairs().play_games(&mut players);
+        assert_eq!(seeding(&result), vec![1, 0]);
+    }
+
+    #[test]
+    fn single_elimination() {
+        let strategies = field();
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[0, 1, 2, 3, 4],
+            Elimination::Single,
+            DrawTiebreak::HigherSeed,
+        );
+        // Five players need a bracket of eight: three byes, four matches.
+        assert_eq!(bracket.byes.len(), 3);
+        assert_eq!(bracket.matches.len(), 4);
+        assert_eq!(bracket.champion, 1);
+        assert_eq!(bracket.seeds, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn draws_go_to_higher_seed() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Naive {})];
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[1, 0],
+            Elimination::Single,
+            DrawTiebreak::HigherSeed,
+        );
+        assert_eq!(bracket.champion, 1);
+    }
+
+    #[test]
+    fn draws_are_replayed() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Naive {})];
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[0, 1],
+            Elimination::Single,
+            DrawTiebreak::Replay { max_replays: 2 },
+        );
+        assert_eq!(bracket.matches.len(), 1);
+        assert_eq!(bracket.matches[0].matches.len(), 3);
+        assert_eq!(bracket.champion, 0);
+    }
+
+    #[test]
+    fn coin_flip_is_reproducible() {
+        let strategies: Vec<Box<dyn Strategy>> = (0..8)
+            .map(|_| Box::new(Naive {}) as Box<dyn Strategy>)
+            .collect();
+        let players = players(&strategies);
+        let tournament = Tournament::builder().seed(5).build().unwrap();
+        let play = || {
+            tournament.play_knockout(
+                &players,
+                &(0..8).collect::<Vec<_>>(),
+                Elimination::Single,
+                DrawTiebreak::CoinFlip,
+            )
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn double_elimination_needs_two_losses() {
+        let strategies = field();
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[0, 1, 2, 3, 4],
+            Elimination::Double,
+            DrawTiebreak::HigherSeed,
+        );
+        let mut losses = vec![0; players.len()];
+        for bracket_match in &bracket.matches {
+            losses[bracket_match.loser()] += 1;
+        }
+        assert!(losses[bracket.champion] <= 1);
+        assert_eq!(losses.iter().filter(|losses| **losses >= 2).count(), 4);
+        assert!(losses.iter().all(|losses| *losses <= 2));
+        assert!(bracket
+            .matches
+            .iter()
+            .any(|bracket_match| bracket_match.section == Section::GrandFinal));
+    }
+
+    #[test]
+    fn single_player_is_champion() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {})];
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[0],
+            Elimination::Double,
+            DrawTiebreak::HigherSeed,
+        );
+        assert!(bracket.matches.is_empty());
+        assert_eq!(bracket.champion, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "every player once")]
+    fn duplicate_seeds() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        Tournament::all_pairs().play_knockout(
+            &players(&strategies),
+            &[0, 0],
+            Elimination::Single,
+            DrawTiebreak::HigherSeed,
+        );
+    }
+
+    #[test]
+    fn display() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let players = players(&strategies);
+        let bracket = Tournament::all_pairs().play_knockout(
+            &players,
+            &[0, 1],
+            Elimination::Single,
+            DrawTiebreak::HigherSeed,
+        );
+        assert_eq!(
+            bracket.to_string(),
+            "Winners round 1\n  (1) player_0 vs (2) player_1: 0-50 -> player_1\nChampion: (2) player_1"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use super::{MatchLength, Tournament, TournamentResult};
+use crate::player::Player;
+use crate::progress::Monitor;
+use crate::statistics;
+use crate::strategy::{all_strategies, Strategy};
+use std::fmt;
+
+const STANDARD_SEED: u64 = 0;
+const STANDARD_REPETITIONS: u32 = 5;
+const STANDARD_NOISE: f64 = 0.05;
+const STANDARD_LONG_MATCH: u32 = 200;
+const STANDARD_CONTINUATION_PROBABILITY: f64 = 0.9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpponentReport {
+    pub opponent: String,
+    // Payoffs per turn of the candidate and of the opponent.
+    pub scores: (f64, f64),
+    pub cooperation_rates: (f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantReport {
+    pub variant: String,
+    pub opponents: Vec<OpponentReport>,
+    // Payoff per turn of the candidate against the whole panel.
+    pub score: f64,
+    // Rank of the candidate by payoff per turn, had it taken part in the
+    // panel's round robin. 1 is the best, ties are averaged.
+    pub rank: f64,
+    pub players: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeReport {
+    pub candidate: String,
+    pub variants: Vec<VariantReport>,
+}
+
+struct Variant {
+    name: String,
+    tournament: Tournament,
+    baseline: TournamentResult,
+}
+
+// A fixed set of opponents, played under several tournament variants. The
+// round robin of the panel is played once, every candidate then only plays
+// the panel.
+pub struct ProbePanel {
+    strategies: Vec<Box<dyn Strategy>>,
+    variants: Vec<Variant>,
+}
+
+impl ProbePanel {
+    pub fn new(strategies: Vec<Box<dyn Strategy>>, variants: Vec<(String, Tournament)>) -> Self {
+        let variants = variants
+            .into_iter()
+            .map(|(name, tournament)| {
+                let mut players = panel_players(&strategies);
+                let baseline = tournament.play_games(&mut players);
+                Variant {
+                    name,
+                    tournament,
+                    baseline,
+                }
+            })
+            .collect();
+        Self {
+            strategies,
+            variants,
+        }
+    }
+
+    // All strategies in the default tournament, with noise, in long matches
+    // and in matches of random length, all seeded.
+    pub fn standard() -> Self {
+        let builder = || {
+            Tournament::builder()
+                .repetitions(STANDARD_REPETITIONS)
+                .seed(STANDARD_SEED)
+        };
+        let variants = vec![
+            ("standard", builder()),
+            ("noisy", builder().noise(STANDARD_NOISE)),
+            ("long", builder().match_length(STANDARD_LONG_MATCH)),
+            (
+                "random length",
+                builder().match_length_distribution(MatchLength::Geometric {
+                    continuation_probability: STANDARD_CONTINUATION_PROBABILITY,
+                }),
+            ),
+        ];
+        Self::new(
+            all_strategies(),
+            variants
+                .into_iter()
+                .map(|(name, builder)| {
+                    let tournament = builder.build().expect("valid standard variant");
+                    (name.to_string(), tournament)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn probe(&self, candidate: &dyn Strategy) -> ProbeReport {
+        ProbeReport {
+            candidate: candidate.name(),
+            variants: self
+                .variants
+                .iter()
+                .map(|variant| self.probe_variant(candidate, variant))
+                .collect(),
+        }
+    }
+
+    fn probe_variant(&self, candidate: &dyn Strategy, variant: &Variant) -> VariantReport {
+        let panel_size = self.strategies.len();
+        let mut players = panel_players(&self.strategies);
+        players.push(Player::new(&candidate.name(), candidate));
+        let mut schedule = Vec::new();
+        for repetition in 0..variant.tournament.repetitions {
+            schedule.extend((0..panel_size).map(|opponent| (repetition, (panel_size, opponent))));
+        }
+        let result = variant
+            .tournament
+            .play_schedule(&mut players, schedule, &Monitor::default());
+        let per_turn = |payoff: u64, turns: u64| match turns {
+            0 => 0.0,
+            turns => payoff as f64 / turns as f64,
+        };
+        let opponents = (0..panel_size)
+            .map(|opponent| {
+                let matches = result
+                    .matches
+                    .iter()
+                    .filter(|match_result| match_result.players.1 == opponent);
+                let (mut scores, mut cooperations, mut turns) = ((0, 0), (0, 0), 0);
+                for match_result in matches {
+                    scores.0 += match_result.scores.0;
+                    scores.1 += match_result.scores.1;
+                    cooperations.0 += match_result.cooperations.0;
+                    cooperations.1 += match_result.cooperations.1;
+                    turns += match_result.turns;
+                }
+                OpponentReport {
+                    opponent: result.players[opponent].clone(),
+                    scores: (per_turn(scores.0, turns), per_turn(scores.1, turns)),
+                    cooperation_rates: (
+                        per_turn(cooperations.0, turns),
+                        per_turn(cooperations.1, turns),
+                    ),
+                }
+            })
+            .collect();
+        // Scores of the joint round robin: what the panel scored among itself
+        // plus what it scored against the candidate.
+        let baseline = &variant.baseline;
+        let mut joint_scores: Vec<f64> = (0..panel_size)
+            .map(|player| {
+                per_turn(
+                    baseline.totals[player] + result.totals[player],
+                    baseline.turns[player] + result.turns[player],
+                )
+            })
+            .collect();
+        let score = per_turn(result.totals[panel_size], result.turns[panel_size]);
+        joint_scores.push(score);
+        VariantReport {
+            variant: variant.name.clone(),
+            opponents,
+            score,
+            rank: statistics::ranks(&joint_scores)[panel_size],
+            players: panel_size + 1,
+        }
+    }
+}
+
+fn panel_players(strategies: &[Box<dyn Strategy>]) -> Vec<Player<'_>> {
+    strategies
+        .iter()
+        .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+        .collect()
+}
+
+impl fmt::Display for ProbeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Probe of {}", self.candidate)?;
+        for variant in &self.variants {
+            write!(
+                f,
+                "\n\n{}: {:.3} per turn, rank {} of {}",
+                variant.variant, variant.score, variant.rank, variant.players
+            )?;
+            for opponent in &variant.opponents {
+                write!(
+                    f,
+                    "\n  vs {:<12} {:.3} - {:.3}, cooperation {:>5.1}% - {:>5.1}%",
+                    opponent.opponent,
+                    opponent.scores.0,
+                    opponent.scores.1,
+                    opponent.cooperation_rates.0 * 100.0,
+                    opponent.cooperation_rates.1 * 100.0
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+
+    #[test]
+    fn ranks_candidate_against_panel() {
+        let panel: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(TitForTat {})];
+        let panel = ProbePanel::new(panel, vec![("plain".to_string(), Tournament::all_pairs())]);
+        let report = panel.probe(&Nasty {});
+        assert_eq!(report.candidate, "Nasty");
+        let variant = &report.variants[0];
+        // Nasty: 50 + 14 in 20 turns. Naive: 30 + 0, TitForTat: 30 + 9.
+        assert_eq!(variant.score, 3.2);
+        assert_eq!(variant.rank, 1.0);
+        assert_eq!(variant.players, 3);
+        assert_eq!(variant.opponents[0].opponent, "Naive");
+        assert_eq!(variant.opponents[0].scores, (5.0, 0.0));
+        assert_eq!(variant.opponents[1].scores, (1.4, 0.9));
+        assert_eq!(variant.opponents[1].cooperation_rates, (0.0, 0.1));
+    }
+
+    #[test]
+    fn candidate_ranked_last() {
+        let panel: Vec<Box<dyn Strategy>> = vec![Box::new(Nasty {}), Box::new(TitForTat {})];
+        let panel = ProbePanel::new(panel, vec![("plain".to_string(), Tournament::all_pairs())]);
+        assert_eq!(panel.probe(&Naive {}).variants[0].rank, 3.0);
+    }
+
+    #[test]
+    fn standard_panel_is_reproducible() {
+        let report = ProbePanel::standard().probe(&TitForTat {});
+        assert_eq!(report.variants.len(), 4);
+        assert_eq!(report, ProbePanel::standard().probe(&TitForTat {}));
+    }
+}
@@ -0,0 +1,263 @@
+use super::TournamentResult;
+use crate::statistics;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedPlayer {
+    pub player: usize,
+    pub name: String,
+    pub strategy: String,
+    // Payoff per turn in every repetition of the tournament.
+    pub normalized_scores: Vec<f64>,
+    pub median_score: f64,
+    // Median payoff per turn after complexity costs, equal to the median
+    // score when complexity is free.
+    #[serde(default)]
+    pub median_adjusted_score: f64,
+    // Median over the repetitions of the player's rank by adjusted score in
+    // each of them.
+    pub median_rank: f64,
+    // Matches in which the player outscored its opponent, twins excluded.
+    pub wins: u32,
+    pub cooperation_rate: f64,
+    #[serde(default)]
+    pub disqualified: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ranking {
+    // Ordered by median adjusted score, then wins, with disqualified players
+    // last.
+    pub players: Vec<RankedPlayer>,
+}
+
+impl TournamentResult {
+    pub fn ranking(&self) -> Ranking {
+        let size = self.players.len();
+        let repetitions = self
+            .matches
+            .iter()
+            .map(|result| result.repetition as usize + 1)
+            .max()
+            .unwrap_or(1);
+        let mut payoffs = vec![vec![0u64; size]; repetitions];
+        let mut turns = vec![vec![0u64; size]; repetitions];
+        let mut wins = vec![0; size];
+        let mut cooperations = vec![0; size];
+        let mut matches = vec![vec![0u32; size]; repetitions];
+        for result in &self.matches {
+            let repetition = result.repetition as usize;
+            let (first, second) = result.players;
+            let played = u32::from(result.turns > 0);
+            payoffs[repetition][first] += result.scores.0;
+            turns[repetition][first] += result.turns;
+            matches[repetition][first] += played;
+            cooperations[first] += result.cooperations.0;
+            if result.is_self_play() {
+                continue;
+            }
+            payoffs[repetition][second] += result.scores.1;
+            turns[repetition][second] += result.turns;
+            matches[repetition][second] += played;
+            cooperations[second] += result.cooperations.1;
+            if result.scores.0 > result.scores.1 {
+                wins[first] += 1;
+            } else if result.scores.1 > result.scores.0 {
+                wins[second] += 1;
+            }
+        }
+        let per_turn = |payoff: f64, turns: u64| match turns {
+            0 => 0.0,
+            turns => payoff / turns as f64,
+        };
+        let normalized: Vec<Vec<f64>> = (0..repetitions)
+            .map(|repetition| {
+                (0..size)
+                    .map(|player| {
+                        per_turn(
+                            payoffs[repetition][player] as f64,
+                            turns[repetition][player],
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let adjusted: Vec<Vec<f64>> = (0..repetitions)
+            .map(|repetition| {
+                (0..size)
+                    .map(|player| {
+                        let turn_cost = self.turn_costs.get(player).copied().unwrap_or(0.0);
+                        let cost = turn_cost * turns[repetition][player] as f64
+                            + self.match_cost * f64::from(matches[repetition][player]);
+                        per_turn(
+                            payoffs[repetition][player] as f64 - cost,
+                            turns[repetition][player],
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let ranks: Vec<Vec<f64>> = adjusted
+            .iter()
+            .map(|scores| statistics::ranks(scores))
+            .collect();
+        let mut players: Vec<_> = (0..size)
+            .map(|player| {
+                let normalized_scores: Vec<f64> =
+                    normalized.iter().map(|scores| scores[player]).collect();
+                let adjusted_scores: Vec<f64> =
+                    adjusted.iter().map(|scores| scores[player]).collect();
+                let player_ranks: Vec<f64> = ranks.iter().map(|ranks| ranks[player]).collect();
+                RankedPlayer {
+                    player,
+                    name: self.players[player].clone(),
+                    strategy: self.strategies[player].clone(),
+                    median_score: statistics::median(&normalized_scores),
+                    median_adjusted_score: statistics::median(&adjusted_scores),
+                    median_rank: statistics::median(&player_ranks),
+                    normalized_scores,
+                    wins: wins[player],
+                    cooperation_rate: match self.turns[player] {
+                        0 => 0.0,
+                        turns => cooperations[player] as f64 / turns as f64,
+                    },
+                    disqualified: self.disqualified.contains(&player),
+                }
+            })
+            .collect();
+        players.sort_by(|a, b| {
+            a.disqualified
+                .cmp(&b.disqualified)
+                .then(b.median_adjusted_score.total_cmp(&a.median_adjusted_score))
+                .then(b.wins.cmp(&a.wins))
+                .then(a.player.cmp(&b.player))
+        });
+        Ranking { players }
+    }
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .players
+            .iter()
+            .map(|player| player.name.len())
+            .chain(["Player".len()])
+            .max()
+            .unwrap_or(0);
+        // The adjusted scores are only shown when complexity has a cost.
+        let charged = self
+            .players
+            .iter()
+            .any(|player| player.median_adjusted_score != player.median_score);
+        write!(f, "Rank  {:<width$}  Median score", "Player")?;
+        if charged {
+            write!(f, "  Adjusted score")?;
+        }
+        write!(f, "  Median rank  Wins  Cooperation")?;
+        for (rank, player) in self.players.iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4}  {:<width$}  {:>12.3}",
+                rank + 1,
+                player.name,
+                player.median_score
+            )?;
+            if charged {
+                write!(f, "  {:>14.3}", player.median_adjusted_score)?;
+            }
+            write!(
+                f,
+                "  {:>11.1}  {:>4}  {:>10.1}%",
+                player.median_rank,
+                player.wins,
+                player.cooperation_rate * 100.0
+            )?;
+            if player.disqualified {
+                write!(f, "  disqualified")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player::Player;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::tournament::{ComplexityCost, Tournament};
+
+    #[test]
+    fn ranks_by_median_normalized_score() {
+        let (naive, nasty, tit_for_tat) = (Naive {}, Nasty {}, TitForTat {});
+        let mut players = vec![
+            Player::new("naive", &naive),
+            Player::new("nasty", &nasty),
+            Player::new("tit_for_tat", &tit_for_tat),
+        ];
+        let result = Tournament::builder()
+            .repetitions(3)
+            .build()
+            .unwrap()
+            .play_games(&mut players);
+        let ranking = result.ranking();
+        let order: Vec<_> = ranking.players.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(order, vec!["nasty", "tit_for_tat", "naive"]);
+        // Nasty: 50 + 14 over 20 turns. TitForTat: 30 + 9 over 20 turns.
+        let nasty = &ranking.players[0];
+        assert_eq!(nasty.normalized_scores, vec![3.2; 3]);
+        assert_eq!(nasty.median_score, 3.2);
+        assert_eq!(nasty.median_rank, 1.0);
+        assert_eq!(nasty.wins, 6);
+        assert_eq!(nasty.cooperation_rate, 0.0);
+        let tit_for_tat = &ranking.players[1];
+        assert_eq!(tit_for_tat.median_score, 1.95);
+        assert_eq!(tit_for_tat.wins, 0);
+        assert_eq!(tit_for_tat.cooperation_rate, 0.55);
+    }
+
+    #[test]
+    fn table() {
+        let naive = Naive {};
+        let mut players = vec![Player::new("naive", &naive)];
+        let result = Tournament::axelrod_tournament().play_games(&mut players);
+        assert_eq!(
+            result.ranking().to_string(),
+            "Rank  Player  Median score  Median rank  Wins  Cooperation\n   \
+             1  naive          3.000          1.0     0       100.0%"
+        );
+    }
+
+    #[test]
+    fn complexity_costs() {
+        let (naive, tit_for_tat) = (Naive {}, TitForTat {});
+        let mut players = vec![
+            Player::new("tit_for_tat", &tit_for_tat),
+            Player::new("naive", &naive),
+        ];
+        let result = Tournament::builder()
+            .complexity_cost(ComplexityCost {
+                per_memory_turn: 0.5,
+                per_state: 0.0,
+                per_match: 1.0,
+            })
+            .build()
+            .unwrap()
+            .play_games(&mut players);
+        assert_eq!(result.costs(), vec![6.0, 1.0]);
+        let ranking = result.ranking();
+        assert_eq!(ranking.players[0].name, "naive");
+        assert_eq!(ranking.players[0].median_score, 3.0);
+        assert_eq!(ranking.players[0].median_adjusted_score, 2.9);
+        assert_eq!(ranking.players[1].median_adjusted_score, 2.4);
+        assert_eq!(
+            ranking.to_string(),
+            "Rank  Player       Median score  Adjusted score  Median rank  Wins  Cooperation\n   \
+             1  naive               3.000           2.900          1.0     0       100.0%\n   \
+             2  tit_for_tat         3.000           2.400          2.0     0       100.0%"
+        );
+    }
+}
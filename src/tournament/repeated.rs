@@ -0,0 +1,130 @@
+use super::Tournament;
+use crate::player::Player;
+use crate::rng;
+use crate::statistics::{self, Summary};
+use crate::strategy::Strategy;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyReport {
+    pub strategy: String,
+    // Score and rank of the strategy in every run, in run order.
+    pub scores: Vec<f64>,
+    pub ranks: Vec<f64>,
+    pub score: Summary,
+    pub rank: Summary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatedTournamentReport {
+    pub runs: u32,
+    // Ordered by mean rank, best first.
+    pub strategies: Vec<StrategyReport>,
+}
+
+impl Tournament {
+    // Same tournament with a different seed.
+    pub fn reseeded(&self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self.clone()
+        }
+    }
+
+    // Plays the tournament `runs` times between one player per strategy. When
+    // the tournament is seeded, run `i` uses `seed + i` so every run differs
+    // but the whole report is reproducible.
+    pub fn play_repeated(
+        &self,
+        strategies: &[Box<dyn Strategy>],
+        runs: u32,
+    ) -> RepeatedTournamentReport {
+        assert!(runs > 0, "at least one run");
+        let mut scores = vec![Vec::new(); strategies.len()];
+        let mut ranks = vec![Vec::new(); strategies.len()];
+        for run in 0..runs {
+            let tournament = match self.seed {
+                Some(seed) => self.reseeded(seed.wrapping_add(u64::from(run))),
+                None => self.clone(),
+            };
+            let mut players: Vec<_> = strategies
+                .iter()
+                .map(|strategy| Player::new(&strategy.name(), strategy.as_ref()))
+                .collect();
+            let result = tournament.play_games(&mut players);
+            let run_scores = tournament.scores(&result);
+            let run_ranks = statistics::ranks(&run_scores);
+            for (i, (score, rank)) in run_scores.into_iter().zip(run_ranks).enumerate() {
+                scores[i].push(score);
+                ranks[i].push(rank);
+            }
+        }
+        // The bootstrap intervals draw random numbers too.
+        if let Some(seed) = self.seed {
+            rng::seed(rng::derive_seed(seed, u64::MAX));
+        }
+        let mut reports: Vec<_> = strategies
+            .iter()
+            .zip(scores.into_iter().zip(ranks))
+            .map(|(strategy, (scores, ranks))| StrategyReport {
+                strategy: strategy.name(),
+                score: Summary::from_samples(&scores),
+                rank: Summary::from_samples(&ranks),
+                scores,
+                ranks,
+            })
+            .collect();
+        reports.sort_by(|a, b| a.rank.mean.total_cmp(&b.rank.mean));
+        RepeatedTournamentReport {
+            runs,
+            strategies: reports,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::random::Random;
+    use crate::strategy::tit_for_tat::TitForTat;
+
+    #[test]
+    fn deterministic_strategies() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let report = Tournament::all_pairs().play_repeated(&strategies, 5);
+        assert_eq!(report.runs, 5);
+        assert_eq!(report.strategies[0].strategy, "Nasty");
+        assert_eq!(report.strategies[0].scores, vec![50.0; 5]);
+        assert_eq!(report.strategies[0].score.std_dev, 0.0);
+        assert_eq!(report.strategies[0].score.confidence_interval, (50.0, 50.0));
+        assert_eq!(report.strategies[0].rank.mean, 1.0);
+        assert_eq!(report.strategies[1].strategy, "Naive");
+        assert_eq!(report.strategies[1].score.max, 0.0);
+        assert_eq!(report.strategies[1].rank.median, 2.0);
+    }
+
+    #[test]
+    fn seeded_runs_differ_but_reproduce() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Random {}),
+            Box::new(TitForTat {}),
+            Box::new(Naive {}),
+        ];
+        let tournament = Tournament::builder()
+            .match_length(50)
+            .seed(3)
+            .build()
+            .unwrap();
+        let report = tournament.play_repeated(&strategies, 10);
+        assert_eq!(report, tournament.play_repeated(&strategies, 10));
+        let random = report
+            .strategies
+            .iter()
+            .find(|report| report.strategy == "Random")
+            .unwrap();
+        assert!(random.score.std_dev > 0.0);
+        assert!(random.score.min <= random.score.confidence_interval.0);
+        assert!(random.score.confidence_interval.1 <= random.score.max);
+    }
+}
@@ -0,0 +1,243 @@
+use super::Incident;
+use crate::cycle::MatchSummary;
+use crate::game::Move;
+use crate::player::{GamePlay, Player, Round};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub repetition: u32,
+    // Indices into `TournamentResult::players`. A match against a player's
+    // own twin has both indices equal, the second entries then belong to the
+    // twin.
+    pub players: (usize, usize),
+    pub scores: (u64, u64),
+    pub turns: u64,
+    pub cooperations: (u64, u64),
+    pub defections: (u64, u64),
+    // Every round of the match, when the tournament records moves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<Vec<Round>>,
+    // Faults of the players in this match, which then forfeited it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incidents: Vec<Incident>,
+}
+
+impl MatchResult {
+    // Builds the result of a match from the history of its first player.
+    pub fn from_history(
+        repetition: u32,
+        players: (usize, usize),
+        scores: (u64, u64),
+        history: &[GamePlay],
+    ) -> Self {
+        let count = |select: fn(&GamePlay) -> Move, expected: Move| {
+            history
+                .iter()
+                .filter(|game| select(game) == expected)
+                .count() as u64
+        };
+        Self {
+            repetition,
+            players,
+            scores,
+            turns: history.len() as u64,
+            cooperations: (
+                count(|game| game.my_move, Move::Collaborate),
+                count(|game| game.their_move, Move::Collaborate),
+            ),
+            defections: (
+                count(|game| game.my_move, Move::Defect),
+                count(|game| game.their_move, Move::Defect),
+            ),
+            rounds: None,
+            incidents: Vec::new(),
+        }
+    }
+
+    pub fn from_summary(repetition: u32, players: (usize, usize), summary: &MatchSummary) -> Self {
+        let turns = summary.turns;
+        let cooperations = summary.cooperations;
+        Self {
+            repetition,
+            players,
+            scores: summary.scores,
+            turns,
+            cooperations,
+            defections: (turns - cooperations.0, turns - cooperations.1),
+            rounds: None,
+            incidents: Vec::new(),
+        }
+    }
+
+    pub fn is_self_play(&self) -> bool {
+        self.players.0 == self.players.1
+    }
+
+    // Makes the match count for nothing, keeping its rounds and incidents.
+    pub fn void(&mut self) {
+        self.scores = (0, 0);
+        self.turns = 0;
+        self.cooperations = (0, 0);
+        self.defections = (0, 0);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentResult {
+    pub players: Vec<String>,
+    pub strategies: Vec<String>,
+    pub matches: Vec<MatchResult>,
+    // `score_matrix[i][j]` is what player `i` scored against player `j` over
+    // all repetitions. The diagonal holds the scores against twins.
+    pub score_matrix: Vec<Vec<u64>>,
+    pub totals: Vec<u64>,
+    pub turns: Vec<u64>,
+    // Whether the tournament was cancelled before all matches were played.
+    #[serde(default)]
+    pub cancelled: bool,
+    // Players removed for a fault, whose matches were all voided.
+    #[serde(default)]
+    pub disqualified: Vec<usize>,
+    // Complexity cost of every player per turn, and the cost of a match.
+    #[serde(default)]
+    pub turn_costs: Vec<f64>,
+    #[serde(default)]
+    pub match_cost: f64,
+}
+
+impl TournamentResult {
+    pub fn new(players: &[Player]) -> Self {
+        let size = players.len();
+        Self {
+            players: players.iter().map(|player| player.name.clone()).collect(),
+            strategies: players.iter().map(Player::strategy_name).collect(),
+            matches: Vec::new(),
+            score_matrix: vec![vec![0; size]; size],
+            totals: vec![0; size],
+            turns: vec![0; size],
+            cancelled: false,
+            disqualified: Vec::new(),
+            turn_costs: vec![0.0; size],
+            match_cost: 0.0,
+        }
+    }
+
+    pub fn add_match(&mut self, result: MatchResult) {
+        let (first, second) = result.players;
+        self.score_matrix[first][second] += result.scores.0;
+        self.totals[first] += result.scores.0;
+        self.turns[first] += result.turns;
+        if !result.is_self_play() {
+            self.score_matrix[second][first] += result.scores.1;
+            self.totals[second] += result.scores.1;
+            self.turns[second] += result.turns;
+        }
+        self.matches.push(result);
+    }
+
+    pub fn incidents(&self) -> impl Iterator<Item = &Incident> {
+        self.matches.iter().flat_map(|result| &result.incidents)
+    }
+
+    // Complexity costs charged to every player over the whole tournament.
+    // Voided matches cost nothing.
+    pub fn costs(&self) -> Vec<f64> {
+        let mut matches = vec![0u32; self.totals.len()];
+        for result in self.matches.iter().filter(|result| result.turns > 0) {
+            matches[result.players.0] += 1;
+            if !result.is_self_play() {
+                matches[result.players.1] += 1;
+            }
+        }
+        (0..self.totals.len())
+            .map(|player| {
+                let turn_cost = self.turn_costs.get(player).copied().unwrap_or(0.0);
+                let turns = self.turns.get(player).copied().unwrap_or(0);
+                turn_cost * turns as f64 + self.match_cost * f64::from(matches[player])
+            })
+            .collect()
+    }
+
+    pub fn per_turn_averages(&self) -> Vec<f64> {
+        self.totals
+            .iter()
+            .zip(&self.turns)
+            .map(|(total, turns)| match turns {
+                0 => 0.0,
+                turns => *total as f64 / *turns as f64,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+
+    fn game(my_move: Move, their_move: Move) -> GamePlay {
+        GamePlay {
+            my_move,
+            their_move,
+        }
+    }
+
+    #[test]
+    fn match_from_history() {
+        let history = [
+            game(Move::Collaborate, Move::Defect),
+            game(Move::Defect, Move::Defect),
+            game(Move::Defect, Move::Collaborate),
+        ];
+        let result = MatchResult::from_history(1, (0, 2), (6, 6), &history);
+        assert_eq!(result.repetition, 1);
+        assert_eq!(result.turns, 3);
+        assert_eq!(result.cooperations, (1, 1));
+        assert_eq!(result.defections, (2, 2));
+        assert!(!result.is_self_play());
+    }
+
+    #[test]
+    fn add_matches() {
+        let (naive, nasty) = (Naive {}, Nasty {});
+        let players = [Player::new("naive", &naive), Player::new("nasty", &nasty)];
+        let mut result = TournamentResult::new(&players);
+        assert_eq!(result.strategies, vec!["Naive", "Nasty"]);
+        result.add_match(MatchResult::from_history(
+            0,
+            (0, 1),
+            (0, 10),
+            &[game(Move::Collaborate, Move::Defect); 2],
+        ));
+        result.add_match(MatchResult::from_history(
+            0,
+            (1, 1),
+            (2, 2),
+            &[game(Move::Defect, Move::Defect); 2],
+        ));
+        assert_eq!(result.score_matrix, vec![vec![0, 0], vec![10, 2]]);
+        assert_eq!(result.totals, vec![0, 12]);
+        assert_eq!(result.turns, vec![2, 4]);
+        assert_eq!(result.per_turn_averages(), vec![0.0, 3.0]);
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let naive = Naive {};
+        let players = [Player::new("naive", &naive)];
+        let mut result = TournamentResult::new(&players);
+        result.add_match(MatchResult::from_history(
+            0,
+            (0, 0),
+            (3, 3),
+            &[game(Move::Collaborate, Move::Collaborate)],
+        ));
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TournamentResult>(&json).unwrap(),
+            result
+        );
+    }
+}
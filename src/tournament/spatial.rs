@@ -0,0 +1,81 @@
+use super::{Tournament, TournamentResult};
+use crate::graph::Graph;
+use crate::player::Player;
+use crate::progress::Monitor;
+
+impl Tournament {
+    // Plays the tournament on an interaction graph: player `i` sits on node
+    // `i` and only meets the players on neighboring nodes. Everything else,
+    // from match length to twins and repetitions, works as in `play_games`,
+    // so the same players can be compared with and without the graph.
+    pub fn play_on_graph(&self, players: &mut [Player], graph: &Graph) -> TournamentResult {
+        assert_eq!(
+            players.len(),
+            graph.len(),
+            "every node of the graph needs exactly one player"
+        );
+        let edges = graph.edges();
+        let mut schedule = Vec::new();
+        for repetition in 0..self.repetitions {
+            schedule.extend(edges.iter().map(|edge| (repetition, *edge)));
+            if self.play_twin {
+                schedule.extend((0..players.len()).map(|i| (repetition, (i, i))));
+            }
+        }
+        self.play_schedule(players, schedule, &Monitor::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Boundary, Neighborhood};
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+
+    #[test]
+    fn players_only_meet_neighbors() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let mut players = vec![
+            Player::new("naive_1", &naive_strategy),
+            Player::new("nasty", &nasty_strategy),
+            Player::new("naive_2", &naive_strategy),
+            Player::new("naive_3", &naive_strategy),
+        ];
+        let path = Graph::from_edge_list("0 1\n1 2\n2 3").unwrap();
+        let result = Tournament::all_pairs().play_on_graph(&mut players, &path);
+        assert_eq!(result.matches.len(), 3);
+        assert_eq!(result.totals, vec![0, 100, 30, 30]);
+        assert_eq!(players[1].score(), 100);
+        assert_eq!(result.score_matrix[1][3], 0);
+    }
+
+    #[test]
+    fn complete_graph_matches_round_robin() {
+        let nasty_strategy = Nasty {};
+        let naive_strategy = Naive {};
+        let tournament = Tournament::axelrod_tournament();
+        let new_players = || {
+            vec![
+                Player::new("naive", &naive_strategy),
+                Player::new("nasty_1", &nasty_strategy),
+                Player::new("nasty_2", &nasty_strategy),
+            ]
+        };
+        let complete = Graph::ring(3, 1);
+        let spatial = tournament.play_on_graph(&mut new_players(), &complete);
+        let round_robin = tournament.play_games(&mut new_players());
+        assert_eq!(spatial.totals, round_robin.totals);
+        assert_eq!(spatial.score_matrix, round_robin.score_matrix);
+    }
+
+    #[test]
+    #[should_panic]
+    fn graph_size_must_match_players() {
+        let naive_strategy = Naive {};
+        let mut players = vec![Player::new("naive", &naive_strategy)];
+        let grid = Graph::grid(2, 2, Neighborhood::VonNeumann, Boundary::Fixed);
+        Tournament::all_pairs().play_on_graph(&mut players, &grid);
+    }
+}
@@ -0,0 +1,302 @@
+use super::{MatchResult, Tournament};
+use crate::player::Player;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+const WIN_POINTS: f64 = 1.0;
+const DRAW_POINTS: f64 = 0.5;
+const BYE_POINTS: f64 = 1.0;
+// Most opponents tried while searching a round without rematches, after
+// which the round is paired greedily.
+const PAIRING_STEPS: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwissRound {
+    pub matches: Vec<MatchResult>,
+    pub bye: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwissStanding {
+    pub player: usize,
+    pub name: String,
+    pub strategy: String,
+    // One point per match won, half a point per draw and one point per bye.
+    pub points: f64,
+    // Sum of the final points of every opponent met.
+    pub buchholz: f64,
+    pub total_payoff: u64,
+    pub opponents: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwissResult {
+    pub rounds: Vec<SwissRound>,
+    // Ranked by points, then Buchholz, then total payoff.
+    pub standings: Vec<SwissStanding>,
+}
+
+impl Tournament {
+    // Plays `rounds` rounds of a Swiss-system tournament. Every round pairs
+    // players with similar points who haven't met yet; with an odd number of
+    // players the lowest ranked player without a bye sits the round out.
+    // Only the match settings of the tournament are used: there is no
+    // self-play and every pairing is played once.
+    pub fn play_swiss(&self, players: &[Player], rounds: u32) -> SwissResult {
+        let mut standings: Vec<_> = players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| SwissStanding {
+                player: i,
+                name: player.name.clone(),
+                strategy: player.strategy_name(),
+                points: 0.0,
+                buchholz: 0.0,
+                total_payoff: 0,
+                opponents: Vec::new(),
+            })
+            .collect();
+        let mut had_bye = vec![false; players.len()];
+        let mut played_rounds = Vec::new();
+        for round in 0..rounds {
+            let mut order: Vec<usize> = (0..players.len()).collect();
+            order.sort_by(|a, b| compare_running(&standings[*a], &standings[*b]));
+            let bye = if order.len() % 2 == 1 {
+                let position = order
+                    .iter()
+                    .rposition(|player| !had_bye[*player])
+                    .unwrap_or(order.len() - 1);
+                let bye = order.remove(position);
+                had_bye[bye] = true;
+                standings[bye].points += BYE_POINTS;
+                Some(bye)
+            } else {
+                None
+            };
+            let pairs = pair_round(&order, &standings);
+            let first_index = u64::from(round) * players.len() as u64;
+            let matches: Vec<_> = pairs
+                .into_par_iter()
+                .enumerate()
+                .map(|(k, pair)| self.play_match(players, first_index + k as u64, round, pair))
+                .collect();
+            for result in &matches {
+                record_match(&mut standings, result);
+            }
+            played_rounds.push(SwissRound { matches, bye });
+        }
+        let final_points: Vec<f64> = standings.iter().map(|standing| standing.points).collect();
+        for standing in standings.iter_mut() {
+            standing.buchholz = standing
+                .opponents
+                .iter()
+                .map(|opponent| final_points[*opponent])
+                .sum();
+        }
+        standings.sort_by(compare_final);
+        SwissResult {
+            rounds: played_rounds,
+            standings,
+        }
+    }
+}
+
+fn record_match(standings: &mut [SwissStanding], result: &MatchResult) {
+    let (first, second) = result.players;
+    let (first_points, second_points) = match result.scores.0.cmp(&result.scores.1) {
+        Ordering::Greater => (WIN_POINTS, 0.0),
+        Ordering::Equal => (DRAW_POINTS, DRAW_POINTS),
+        Ordering::Less => (0.0, WIN_POINTS),
+    };
+    standings[first].points += first_points;
+    standings[first].total_payoff += result.scores.0;
+    standings[first].opponents.push(second);
+    standings[second].points += second_points;
+    standings[second].total_payoff += result.scores.1;
+    standings[second].opponents.push(first);
+}
+
+fn compare_running(a: &SwissStanding, b: &SwissStanding) -> Ordering {
+    b.points
+        .total_cmp(&a.points)
+        .then(b.total_payoff.cmp(&a.total_payoff))
+        .then(a.player.cmp(&b.player))
+}
+
+fn compare_final(a: &SwissStanding, b: &SwissStanding) -> Ordering {
+    b.points
+        .total_cmp(&a.points)
+        .then(b.buchholz.total_cmp(&a.buchholz))
+        .then(b.total_payoff.cmp(&a.total_payoff))
+        .then(a.player.cmp(&b.player))
+}
+
+fn pair_round(order: &[usize], standings: &[SwissStanding]) -> Vec<(usize, usize)> {
+    let mut steps = PAIRING_STEPS;
+    pair_without_rematches(order, standings, &mut steps)
+        .unwrap_or_else(|| pair_greedily(order, standings))
+}
+
+// Pairs the players in ranking order, each with the closest ranked player it
+// hasn't met yet, backtracking when the remaining players can't be paired.
+// Returns None when every pairing would contain a rematch, or when no
+// pairing was found within `steps` tried opponents.
+fn pair_without_rematches(
+    order: &[usize],
+    standings: &[SwissStanding],
+    steps: &mut u32,
+) -> Option<Vec<(usize, usize)>> {
+    let Some((first, rest)) = order.split_first() else {
+        return Some(Vec::new());
+    };
+    for (position, opponent) in rest.iter().enumerate() {
+        if standings[*first].opponents.contains(opponent) {
+            continue;
+        }
+        if *steps == 0 {
+            return None;
+        }
+        *steps -= 1;
+        let remaining: Vec<usize> = rest
+            .iter()
+            .enumerate()
+            .filter(|(other_position, _)| *other_position != position)
+            .map(|(_, player)| *player)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematches(&remaining, standings, steps) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+// Pairs the players in ranking order, each with the closest ranked player it
+// hasn't met yet, or with the closest ranked player if it met all of them.
+fn pair_greedily(order: &[usize], standings: &[SwissStanding]) -> Vec<(usize, usize)> {
+    let mut remaining = order.to_vec();
+    let mut pairs = Vec::new();
+    while remaining.len() >= 2 {
+        let first = remaining.remove(0);
+        let position = remaining
+            .iter()
+            .position(|opponent| !standings[first].opponents.contains(opponent))
+            .unwrap_or(0);
+        pairs.push((first, remaining.remove(position)));
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::grim_trigger::GrimTrigger;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::nasty::Nasty;
+    use crate::strategy::tit_for_tat::TitForTat;
+    use crate::strategy::Strategy;
+
+    fn players(strategies: &[Box<dyn Strategy>]) -> Vec<Player<'_>> {
+        strategies
+            .iter()
+            .enumerate()
+            .map(|(i, strategy)| Player::new(&format!("player_{}", i), strategy.as_ref()))
+            .collect()
+    }
+
+    #[test]
+    fn no_rematches() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+            Box::new(GrimTrigger {}),
+        ];
+        let result = Tournament::all_pairs().play_swiss(&players(&strategies), 3);
+        assert_eq!(result.rounds.len(), 3);
+        for standing in &result.standings {
+            let mut opponents = standing.opponents.clone();
+            opponents.sort();
+            opponents.dedup();
+            assert_eq!(opponents.len(), 3);
+        }
+        assert_eq!(result.standings[0].strategy, "Nasty");
+        assert_eq!(result.standings[0].points, 3.0);
+    }
+
+    #[test]
+    fn odd_players_get_distinct_byes() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Naive {}),
+            Box::new(Nasty {}),
+            Box::new(TitForTat {}),
+            Box::new(GrimTrigger {}),
+            Box::new(Naive {}),
+        ];
+        let result = Tournament::all_pairs().play_swiss(&players(&strategies), 5);
+        let mut byes: Vec<_> = result
+            .rounds
+            .iter()
+            .map(|round| round.bye.unwrap())
+            .collect();
+        byes.sort();
+        assert_eq!(byes, vec![0, 1, 2, 3, 4]);
+        for round in &result.rounds {
+            assert_eq!(round.matches.len(), 2);
+        }
+    }
+
+    #[test]
+    fn buchholz_breaks_ties() {
+        let standing = |player, points, buchholz| SwissStanding {
+            player,
+            name: String::new(),
+            strategy: String::new(),
+            points,
+            buchholz,
+            total_payoff: 0,
+            opponents: Vec::new(),
+        };
+        let mut standings = [
+            standing(0, 2.0, 3.0),
+            standing(1, 2.0, 4.0),
+            standing(2, 3.0, 1.0),
+        ];
+        standings.sort_by(compare_final);
+        let order: Vec<_> = standings.iter().map(|standing| standing.player).collect();
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn buchholz_sums_opponent_points() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let result = Tournament::all_pairs().play_swiss(&players(&strategies), 1);
+        assert_eq!(result.standings[0].strategy, "Nasty");
+        assert_eq!(result.standings[0].buchholz, 0.0);
+        assert_eq!(result.standings[1].buchholz, 1.0);
+    }
+
+    #[test]
+    fn falls_back_to_rematches() {
+        let strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Naive {}), Box::new(Nasty {})];
+        let result = Tournament::all_pairs().play_swiss(&players(&strategies), 2);
+        assert_eq!(result.rounds[1].matches.len(), 1);
+        assert_eq!(result.standings[0].points, 2.0);
+    }
+
+    #[test]
+    fn forced_late_rounds_are_paired_quickly() {
+        let strategies: Vec<Box<dyn Strategy>> = (0..40)
+            .map(|_| Box::new(Naive {}) as Box<dyn Strategy>)
+            .collect();
+        let result = Tournament::all_pairs().play_swiss(&players(&strategies), 39);
+        for round in &result.rounds {
+            assert_eq!(round.matches.len(), 20);
+        }
+        assert!(result
+            .standings
+            .iter()
+            .all(|standing| standing.opponents.len() == 39));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::game::{Move, Payoff, Timing};
+use crate::player::{self, Player, Round};
+use crate::strategy::find_strategy;
+use crate::tournament::{Tournament, TournamentResult};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+pub const TRANSCRIPT_VERSION: u32 = 1;
+const GRID_WIDTH: usize = 50;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW_BACKGROUND: &str = "\x1b[43m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchTranscript {
+    pub players: (String, String),
+    pub strategies: (String, String),
+    pub repetition: u32,
+    pub rounds: Vec<Round>,
+}
+
+impl MatchTranscript {
+    pub fn scores(&self) -> (u64, u64) {
+        self.rounds.iter().fold((0, 0), |(first, second), round| {
+            (
+                first + u64::from(round.payoffs.0),
+                second + u64::from(round.payoffs.1),
+            )
+        })
+    }
+}
+
+// Every recorded match of a tournament with the settings needed to play them
+// again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub payoff: Payoff,
+    pub noise: f64,
+    #[serde(default)]
+    pub timing: Timing,
+    pub matches: Vec<MatchTranscript>,
+}
+
+#[derive(Debug)]
+pub enum TranscriptError {
+    Io(io::Error),
+    Invalid(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranscriptError::Io(error) => write!(f, "transcript I/O failed: {}", error),
+            TranscriptError::Invalid(error) => write!(f, "transcript is invalid: {}", error),
+            TranscriptError::UnsupportedVersion(version) => {
+                write!(f, "transcript version {} is not supported", version)
+            }
+        }
+    }
+}
+
+impl Error for TranscriptError {}
+
+impl From<io::Error> for TranscriptError {
+    fn from(error: io::Error) -> Self {
+        TranscriptError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    Reproduced,
+    // First round that came out differently.
+    Diverged { round: usize },
+    // Noise or a nondeterministic strategy make the match irreproducible.
+    Nondeterministic,
+    UnknownStrategy(String),
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verification::Reproduced => write!(f, "reproduced"),
+            Verification::Diverged { round } => write!(f, "DIVERGED at round {}", round + 1),
+            Verification::Nondeterministic => write!(f, "not deterministic, not verified"),
+            Verification::UnknownStrategy(name) => write!(f, "unknown strategy {}", name),
+        }
+    }
+}
+
+impl Transcript {
+    // Transcript of the matches of a tournament that records moves.
+    pub fn from_result(tournament: &Tournament, result: &TournamentResult) -> Self {
+        let matches = result
+            .matches
+            .iter()
+            .filter_map(|match_result| {
+                let rounds = match_result.rounds.clone()?;
+                let (first, second) = match_result.players;
+                let second_name = if match_result.is_self_play() {
+                    format!("{}_twin", result.players[second])
+                } else {
+                    result.players[second].clone()
+                };
+                Some(MatchTranscript {
+                    players: (result.players[first].clone(), second_name),
+                    strategies: (
+                        result.strategies[first].clone(),
+                        result.strategies[second].clone(),
+                    ),
+                    repetition: match_result.repetition,
+                    rounds,
+                })
+            })
+            .collect();
+        Self {
+            version: TRANSCRIPT_VERSION,
+            payoff: tournament.payoff().clone(),
+            noise: tournament.noise(),
+            timing: tournament.timing(),
+            matches,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), TranscriptError> {
+        let json = serde_json::to_string_pretty(self).expect("serializable transcript");
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, TranscriptError> {
+        let text = fs::read_to_string(path)?;
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(TranscriptError::Invalid)?;
+        let version = value["version"].as_u64().unwrap_or(0) as u32;
+        if version != TRANSCRIPT_VERSION {
+            return Err(TranscriptError::UnsupportedVersion(version));
+        }
+        serde_json::from_value(value).map_err(TranscriptError::Invalid)
+    }
+
+    // Plays a recorded match again with the strategies of the same names and
+    // checks that every round comes out the same.
+    pub fn verify(&self, transcript: &MatchTranscript) -> Verification {
+        let find = |name: &str| find_strategy(name).ok_or(name.to_string());
+        let strategies = match (
+            find(&transcript.strategies.0),
+            find(&transcript.strategies.1),
+        ) {
+            (Ok(first), Ok(second)) => (first, second),
+            (Err(name), _) | (_, Err(name)) => return Verification::UnknownStrategy(name),
+        };
+        if self.noise > 0.0 || !strategies.0.is_deterministic() || !strategies.1.is_deterministic()
+        {
+            return Verification::Nondeterministic;
+        }
+        let first = Player::new(&transcript.players.0, strategies.0.as_ref());
+        let second = Player::new(&transcript.players.1, strategies.1.as_ref());
+        let turns = transcript.rounds.len() as u32;
+        let replayed = match self.timing {
+            Timing::Simultaneous => player::play_match(&first, &second, &self.payoff, turns, 0.0),
+            Timing::Alternating => player::play_alternating_repetition(
+                &first,
+                &second,
+                &self.payoff,
+                turns,
+                0.0,
+                transcript.repetition,
+            ),
+        };
+        match replayed
+            .rounds
+            .iter()
+            .zip(&transcript.rounds)
+            .position(|(replayed, recorded)| replayed != recorded)
+        {
+            Some(round) => Verification::Diverged { round },
+            None => Verification::Reproduced,
+        }
+    }
+}
+
+// The moves of both players as two rows of C and D, wrapped every
+// `GRID_WIDTH` rounds. With colors, cooperation is green, defection red and
+// a move flipped by noise has a yellow background.
+pub fn render_grid(transcript: &MatchTranscript, colored: bool) -> String {
+    let labels = (
+        format!("{} ({})", transcript.players.0, transcript.strategies.0),
+        format!("{} ({})", transcript.players.1, transcript.strategies.1),
+    );
+    let width = labels.0.len().max(labels.1.len());
+    let cell = |intended: Move, actual: Move| {
+        if !colored {
+            return actual.to_string();
+        }
+        let color = match actual {
+            Move::Collaborate => GREEN,
+            Move::Defect => RED,
+        };
+        let background = if intended != actual {
+            YELLOW_BACKGROUND
+        } else {
+            ""
+        };
+        format!("{}{}{}{}", color, background, actual, RESET)
+    };
+    let mut lines = Vec::new();
+    for rounds in transcript.rounds.chunks(GRID_WIDTH) {
+        let first: String = rounds
+            .iter()
+            .map(|round| cell(round.intended.0, round.actual.0))
+            .collect();
+        let second: String = rounds
+            .iter()
+            .map(|round| cell(round.intended.1, round.actual.1))
+            .collect();
+        lines.push(format!("{:<width$}  {}", labels.0, first));
+        lines.push(format!("{:<width$}  {}", labels.1, second));
+    }
+    let scores = transcript.scores();
+    lines.push(format!(
+        "{:<width$}  {} - {}",
+        "Payoffs", scores.0, scores.1
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::naive::Naive;
+    use crate::strategy::tit_for_tat::TitForTat;
+
+    fn recorded_transcript() -> Transcript {
+        let (naive, tit_for_tat) = (Naive {}, TitForTat {});
+        let mut players = vec![
+            Player::new("naive", &naive),
+            Player::new("tit_for_tat", &tit_for_tat),
+        ];
+        let tournament = Tournament::builder()
+            .match_length(3)
+            .self_play(true)
+            .record_moves(true)
+            .build()
+            .unwrap();
+        let result = tournament.play_games(&mut players);
+        Transcript::from_result(&tournament, &result)
+    }
+
+    #[test]
+    fn records_every_match() {
+        let transcript = recorded_transcript();
+        assert_eq!(transcript.version, TRANSCRIPT_VERSION);
+        assert_eq!(transcript.matches.len(), 3);
+        let twin = &transcript.matches[1];
+        assert_eq!(twin.players, ("naive".into(), "naive_twin".into()));
+        assert_eq!(twin.scores(), (9, 9));
+    }
+
+    #[test]
+    fn round_trip_and_verify() {
+        let transcript = recorded_transcript();
+        let path = std::env::temp_dir().join(format!("transcript_{}.json", std::process::id()));
+        transcript.save(&path).unwrap();
+        let loaded = Transcript::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, transcript);
+        for recorded in &loaded.matches {
+            assert_eq!(loaded.verify(recorded), Verification::Reproduced);
+        }
+        let mut tampered = loaded.matches[0].clone();
+        tampered.rounds[2].actual.0 = Move::Defect;
+        assert_eq!(
+            loaded.verify(&tampered),
+            Verification::Diverged { round: 2 }
+        );
+        tampered.strategies.1 = "Unknown".to_string();
+        assert_eq!(
+            loaded.verify(&tampered),
+            Verification::UnknownStrategy("Unknown".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut transcript = recorded_transcript();
+        transcript.version = TRANSCRIPT_VERSION + 1;
+        let path = std::env::temp_dir().join(format!("transcript_v2_{}.json", std::process::id()));
+        transcript.save(&path).unwrap();
+        let loaded = Transcript::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            loaded,
+            Err(TranscriptError::UnsupportedVersion(version)) if version == TRANSCRIPT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn grid() {
+        let transcript = recorded_transcript();
+        assert_eq!(
+            render_grid(&transcript.matches[0], false),
+            "naive (Naive)            CCC\n\
+             tit_for_tat (TitForTat)  CCC\n\
+             Payoffs                  9 - 9"
+        );
+        let noisy = MatchTranscript {
+            rounds: vec![Round {
+                intended: (Move::Collaborate, Move::Collaborate),
+                actual: (Move::Defect, Move::Collaborate),
+                payoffs: (5, 0),
+            }],
+            ..transcript.matches[0].clone()
+        };
+        let colored = render_grid(&noisy, true);
+        assert!(colored.contains("\x1b[31m\x1b[43mD\x1b[0m"));
+        assert!(colored.contains("\x1b[32mC\x1b[0m"));
+    }
+}